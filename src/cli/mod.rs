@@ -0,0 +1,460 @@
+use std::{
+    fmt,
+    fs::{read, File},
+    io::{self, Read, Write},
+};
+
+use crate::shared::{
+    aes::{
+        decrypt_cbc, decrypt_ecb, detect_ebc, encrypt_cbc, encrypt_ecb, pkcs7_padding, pkcs7_unpad,
+    },
+    analysis::{break_repeating_xor, freq_analysis_iter, KeySizeRange},
+    encoding::{autodetect, Encoding},
+    error::CryptoError,
+    xor::xor_with_repeating_key,
+};
+
+pub const USAGE: &str = "\
+usage: cryptopals <command> [options] [FILE]
+
+Reads FILE, or stdin when it is missing or -, and writes to stdout.
+
+commands:
+  convert               re-encode the input
+  xor                   xor with a repeating --key
+  break-single-xor      find the single byte key, and the plaintext
+  break-repeating-xor   find the repeating key, and the plaintext, with every
+                        key it tried on stderr, best first
+  detect-ecb            the numbers of the lines that repeat a 16 byte block
+  aes-ecb               encrypt under --key, or decrypt with -d
+  aes-cbc               the same, with --iv (all zeros when missing)
+  pad                   PKCS#7 pad to --block-size, or strip it with --unpad
+
+options:
+  --from ENCODING       of the input, auto to guess it (default raw for
+                        plaintext to xor, pad or encrypt, otherwise auto)
+  --to ENCODING         of the output (default raw)
+  --key VALUE           the key, in --key-encoding
+  --iv VALUE            the IV, in --key-encoding
+  --key-encoding ENC    of --key and --iv (default raw)
+  -d, --decrypt         decrypt rather than encrypt
+  --no-pad              leave out PKCS#7 padding around AES
+  --block-size N        for pad (default 16)
+  --unpad               strip the padding rather than add it
+  --max-keysize N       the longest key break-repeating-xor tries (default 40)
+  --top N               how many of the likeliest key sizes it solves (default 3)
+  -o, --output FILE     write to FILE rather than stdout
+
+encodings: hex, base64, base64url, base32, ascii85, decimal, raw
+";
+
+#[derive(Debug)]
+pub enum CliError {
+    Usage(String),
+    Io(io::Error),
+    Crypto(CryptoError),
+    // the input was fine, but nothing in it could be found
+    NotFound(&'static str),
+}
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CliError::Usage(message) => write!(f, "{}", message),
+            CliError::Io(e) => write!(f, "{}", e),
+            CliError::Crypto(e) => write!(f, "{}", e),
+            CliError::NotFound(what) => write!(f, "no {} found", what),
+        }
+    }
+}
+
+impl std::error::Error for CliError {}
+
+impl From<io::Error> for CliError {
+    fn from(e: io::Error) -> Self {
+        CliError::Io(e)
+    }
+}
+
+impl From<CryptoError> for CliError {
+    fn from(e: CryptoError) -> Self {
+        CliError::Crypto(e)
+    }
+}
+
+fn usage<T>(message: String) -> Result<T, CliError> {
+    Err(CliError::Usage(message))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Options {
+    pub command: String,
+    pub input: Option<String>,
+    pub output: Option<String>,
+    // None to guess
+    pub from: Option<Encoding>,
+    pub to: Encoding,
+    pub key: Option<String>,
+    pub iv: Option<String>,
+    pub key_encoding: Encoding,
+    pub decrypt: bool,
+    pub pad: bool,
+    pub block_size: usize,
+    pub unpad: bool,
+    pub max_keysize: usize,
+    pub top: usize,
+}
+
+fn parse_encoding(name: &str) -> Result<Encoding, CliError> {
+    Encoding::from_name(name).map_or_else(|| usage(format!("unknown encoding {}", name)), Ok)
+}
+
+fn parse_number(flag: &str, value: &str) -> Result<usize, CliError> {
+    value
+        .parse()
+        .or_else(|_| usage(format!("{} takes a number, not {}", flag, value)))
+}
+
+const COMMANDS: [&str; 8] = [
+    "convert",
+    "xor",
+    "break-single-xor",
+    "break-repeating-xor",
+    "detect-ecb",
+    "aes-ecb",
+    "aes-cbc",
+    "pad",
+];
+
+// help is a command of its own, so the usage goes to stdout without any input
+pub fn parse_args(args: &[String]) -> Result<Options, CliError> {
+    let command = match args.first().map(String::as_str) {
+        None => return usage(USAGE.to_string()),
+        Some("help" | "-h" | "--help") => "help",
+        Some(command) if COMMANDS.contains(&command) => command,
+        Some(command) => return usage(format!("unknown command {}\n\n{}", command, USAGE)),
+    };
+    let mut options = Options {
+        command: command.to_string(),
+        input: None,
+        output: None,
+        from: None,
+        to: Encoding::Raw,
+        key: None,
+        iv: None,
+        key_encoding: Encoding::Raw,
+        decrypt: false,
+        pad: true,
+        block_size: 16,
+        unpad: false,
+        max_keysize: 40,
+        top: 3,
+    };
+
+    let mut from_given = false;
+    let mut rest = args[1..].iter();
+    while let Some(arg) = rest.next() {
+        let mut value = || {
+            rest.next()
+                .cloned()
+                .map_or_else(|| usage(format!("{} needs a value", arg)), Ok)
+        };
+        match arg.as_str() {
+            "--from" => {
+                from_given = true;
+                let name = value()?;
+                options.from = match name.as_str() {
+                    "auto" => None,
+                    name => Some(parse_encoding(name)?),
+                }
+            }
+            "--to" => options.to = parse_encoding(&value()?)?,
+            "--key" => options.key = Some(value()?),
+            "--iv" => options.iv = Some(value()?),
+            "--key-encoding" => options.key_encoding = parse_encoding(&value()?)?,
+            "-o" | "--output" => options.output = Some(value()?),
+            "--block-size" => options.block_size = parse_number(arg, &value()?)?,
+            "--max-keysize" => options.max_keysize = parse_number(arg, &value()?)?,
+            "--top" => options.top = parse_number(arg, &value()?)?,
+            "-d" | "--decrypt" => options.decrypt = true,
+            "--no-pad" => options.pad = false,
+            "--unpad" => options.unpad = true,
+            flag if flag.starts_with('-') && flag != "-" => {
+                return usage(format!("unknown option {}\n\n{}", flag, USAGE))
+            }
+            _ if options.input.is_some() => return usage(format!("unexpected {}", arg)),
+            path => options.input = Some(path.to_string()),
+        }
+    }
+
+    // short plaintext is too easily taken for base64
+    let plaintext = match options.command.as_str() {
+        "xor" | "pad" => true,
+        "aes-ecb" | "aes-cbc" => !options.decrypt,
+        _ => false,
+    };
+    if plaintext && !from_given {
+        options.from = Some(Encoding::Raw);
+    }
+    Ok(options)
+}
+
+fn decode(encoding: Option<Encoding>, input: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    let encoding =
+        encoding.unwrap_or_else(|| autodetect(input).first().map_or(Encoding::Raw, |(e, _)| *e));
+    encoding.decode(input)
+}
+
+fn decode_key(options: &Options, value: &Option<String>, flag: &str) -> Result<Vec<u8>, CliError> {
+    match value {
+        Some(value) => Ok(options.key_encoding.decode(value.as_bytes())?),
+        None => usage(format!("{} needs {}", options.command, flag)),
+    }
+}
+
+// the single byte key whose plaintext scores best
+fn break_single_xor(ct: &[u8]) -> Option<(f64, u8, String)> {
+    freq_analysis_iter(ct).min_by(|a, b| a.0.partial_cmp(&b.0).unwrap())
+}
+
+// What the command writes out, before the output encoding, and what it says
+// about it on stderr
+fn execute(options: &Options, input: &[u8]) -> Result<(Vec<u8>, Vec<String>), CliError> {
+    let mut notes = Vec::new();
+    let data = match options.command.as_str() {
+        "convert" => decode(options.from, input)?,
+        "xor" => {
+            let key = decode_key(options, &options.key, "--key")?;
+            xor_with_repeating_key(&decode(options.from, input)?, &key)?
+        }
+        "break-single-xor" => {
+            let (score, key, plaintext) = break_single_xor(&decode(options.from, input)?)
+                .ok_or(CliError::NotFound("key giving printable text"))?;
+            notes.push(format!("key 0x{:02x}, score {:.2}", key, score));
+            plaintext.into_bytes()
+        }
+        "break-repeating-xor" => {
+            let key_sizes = KeySizeRange {
+                max: options.max_keysize,
+                top: options.top,
+                ..KeySizeRange::default()
+            };
+            let candidates = break_repeating_xor(&decode(options.from, input)?, key_sizes);
+            for candidate in &candidates {
+                notes.push(format!(
+                    "key {:?}, score {:.2}",
+                    String::from_utf8_lossy(&candidate.key),
+                    candidate.score
+                ));
+            }
+            candidates
+                .into_iter()
+                .next()
+                .ok_or(CliError::NotFound("key giving printable text"))?
+                .plaintext
+        }
+        "detect-ecb" => {
+            // raw input is one ciphertext, anything else one per line
+            let lines = match options.from {
+                Some(Encoding::Raw) => vec![input],
+                _ => input.split(|c| *c == b'\n').collect(),
+            };
+            let mut found = String::new();
+            for (i, line) in lines.into_iter().enumerate() {
+                if !line.trim_ascii().is_empty() && detect_ebc(&decode(options.from, line)?) {
+                    found += &format!("{}\n", i + 1);
+                }
+            }
+            found.into_bytes()
+        }
+        "aes-ecb" | "aes-cbc" => {
+            let key = decode_key(options, &options.key, "--key")?;
+            let iv = match options.iv {
+                Some(_) => decode_key(options, &options.iv, "--iv")?,
+                None => vec![0; 16],
+            };
+            let bytes = decode(options.from, input)?;
+            let cbc = options.command == "aes-cbc";
+            if options.decrypt {
+                let pt = if cbc {
+                    decrypt_cbc(&key, bytes, &iv)?
+                } else {
+                    decrypt_ecb(&key, bytes)?
+                };
+                if options.pad {
+                    pkcs7_unpad(&pt, 16)?
+                } else {
+                    pt
+                }
+            } else {
+                let pt = if options.pad {
+                    pkcs7_padding(&bytes, 16)
+                } else {
+                    bytes
+                };
+                if cbc {
+                    encrypt_cbc(&key, pt, &iv)?
+                } else {
+                    encrypt_ecb(&key, pt)?
+                }
+            }
+        }
+        "pad" => {
+            if !(1..=255).contains(&options.block_size) {
+                return usage(format!("bad block size {}", options.block_size));
+            }
+            let bytes = decode(options.from, input)?;
+            if options.unpad {
+                pkcs7_unpad(&bytes, options.block_size)?
+            } else {
+                pkcs7_padding(&bytes, options.block_size)
+            }
+        }
+        command => return usage(format!("unknown command {}\n\n{}", command, USAGE)),
+    };
+
+    // text gets a newline, so it doesn't run into the prompt
+    let mut out = options.to.encode(&data);
+    if options.to != Encoding::Raw {
+        out.push(b'\n');
+    }
+    Ok((out, notes))
+}
+
+pub fn run(
+    args: &[String],
+    stdin: &mut dyn Read,
+    stdout: &mut dyn Write,
+    stderr: &mut dyn Write,
+) -> Result<(), CliError> {
+    let options = parse_args(args)?;
+    if options.command == "help" {
+        stdout.write_all(USAGE.as_bytes())?;
+        return Ok(());
+    }
+    let input = match options.input.as_deref() {
+        None | Some("-") => {
+            let mut input = Vec::new();
+            stdin.read_to_end(&mut input)?;
+            input
+        }
+        Some(path) => read(path)?,
+    };
+
+    let (out, notes) = execute(&options, &input)?;
+    for note in notes {
+        writeln!(stderr, "{}", note)?;
+    }
+    match &options.output {
+        Some(path) => File::create(path)?.write_all(&out)?,
+        None => stdout.write_all(&out)?,
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use std::io::{self, Read};
+
+    use super::{run, CliError, USAGE};
+    use crate::shared::{error::CryptoError, xor::xor_with_key};
+
+    fn cli(args: &[&str], stdin: &[u8]) -> Result<(Vec<u8>, String), CliError> {
+        let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+        let (mut stdout, mut stderr) = (Vec::new(), Vec::new());
+        run(&args, &mut &stdin[..], &mut stdout, &mut stderr)?;
+        Ok((stdout, String::from_utf8(stderr).unwrap()))
+    }
+
+    // stdin that fails the test if anything reads it
+    struct Untouched;
+
+    impl Read for Untouched {
+        fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+            panic!("read stdin");
+        }
+    }
+
+    fn cli_without_input(args: &[&str]) -> Result<Vec<u8>, CliError> {
+        let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+        let mut stdout = Vec::new();
+        run(&args, &mut Untouched, &mut stdout, &mut Vec::new())?;
+        Ok(stdout)
+    }
+
+    #[test]
+    fn test_run() {
+        let (out, _) = cli(&["convert", "--from", "hex", "--to", "base64"], b"49276d\n").unwrap();
+        assert_eq!(out, b"SSdt\n");
+        let (out, _) = cli(&["xor", "--key", "ICE", "--to", "hex"], b"Burning").unwrap();
+        assert_eq!(out, b"0b3637272a2b2e\n");
+
+        let ct = xor_with_key(b"Cooking MC's like a pound of bacon", 0x58);
+        let (out, notes) = cli(&["break-single-xor", "--from", "raw"], &ct).unwrap();
+        assert_eq!(out, b"Cooking MC's like a pound of bacon");
+        assert!(notes.starts_with("key 0x58"));
+
+        let (out, notes) = cli(&["break-repeating-xor", "src/set1/6.txt"], b"").unwrap();
+        assert!(out.starts_with(b"I'm back and I'm ringin' the bell"));
+        assert!(notes.starts_with("key \"Terminator X: Bring the noise\", score"));
+
+        let args = ["break-repeating-xor", "--top", "1", "src/set1/6.txt"];
+        let (_, notes) = cli(&args, b"").unwrap();
+        assert_eq!(notes.lines().count(), 1);
+
+        let (out, _) = cli(&["detect-ecb", "src/set1/8.txt"], b"").unwrap();
+        assert_eq!(out, b"133\n");
+        let (out, _) = cli(&["detect-ecb", "--to", "hex", "src/set1/8.txt"], b"").unwrap();
+        assert_eq!(out, b"3133330a\n");
+
+        let (ct, _) = cli(
+            &["aes-ecb", "--key", "YELLOW SUBMARINE", "--to", "hex"],
+            b"hi",
+        )
+        .unwrap();
+        assert_eq!(ct.len(), 33);
+        let (out, _) = cli(&["aes-ecb", "-d", "--key", "YELLOW SUBMARINE"], &ct).unwrap();
+        assert_eq!(out, b"hi");
+
+        let (out, _) = cli(&["pad", "--block-size", "4"], b"hi").unwrap();
+        assert_eq!(out, b"hi\x02\x02");
+
+        assert_eq!(cli_without_input(&["help"]).unwrap(), USAGE.as_bytes());
+        assert_eq!(
+            cli_without_input(&["--help", "-d"]).unwrap(),
+            USAGE.as_bytes()
+        );
+    }
+
+    #[test]
+    fn test_run_errors() {
+        // caught before there is any input to wait for
+        assert!(matches!(cli_without_input(&[]), Err(CliError::Usage(_))));
+        assert!(matches!(
+            cli_without_input(&["frob"]),
+            Err(CliError::Usage(_))
+        ));
+        assert!(matches!(
+            cli_without_input(&["convert", "--to", "rot13"]),
+            Err(CliError::Usage(_))
+        ));
+        assert!(matches!(cli(&["xor"], b"hi"), Err(CliError::Usage(_))));
+        assert!(matches!(
+            cli(&["convert", "--from", "hex"], b"4g"),
+            Err(CliError::Crypto(CryptoError::InvalidCharacter {
+                position: 1,
+                ..
+            }))
+        ));
+        assert!(matches!(
+            cli(&["aes-ecb", "--key", "YELLOW"], b"hi"),
+            Err(CliError::Crypto(CryptoError::InvalidKeyLength(6)))
+        ));
+        assert!(matches!(
+            cli(&["convert", "src/set1/missing.txt"], b""),
+            Err(CliError::Io(_))
+        ));
+    }
+}
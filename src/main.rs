@@ -1,7 +1,30 @@
 extern crate core;
 
+use std::{io, process::exit};
+
+use cli::CliError;
+
+pub mod cli;
 pub mod set1;
 pub mod set2;
+pub mod set6;
+pub mod set7;
+pub mod set8;
 pub mod shared;
 
-fn main() {}
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let result = cli::run(
+        &args,
+        &mut io::stdin(),
+        &mut io::stdout(),
+        &mut io::stderr(),
+    );
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        exit(match e {
+            CliError::Usage(_) => 2,
+            _ => 1,
+        });
+    }
+}
@@ -2,16 +2,23 @@
 mod tests {
     use std::fs::read_to_string;
 
-    use base64::{engine::general_purpose, Engine};
     use pretty_assertions::assert_eq;
     use rand::Rng;
 
     use crate::shared::{
         aes::{decrypt_ecb, detect_ebc},
-        analysis::{freq_analysis, freq_analysis_iter, most_likely_encoded},
-        conversion::{bytes_to_base64, bytes_to_hex, hex_to_bytes, transpose},
-        hamming::{hamming_distance, hamming_distance_bytes},
-        xor::{xor, xor_with_key, xor_with_repeating_key},
+        analysis::{
+            break_repeating_xor, freq_analysis, key_size_distance, most_likely_encoded,
+            KeySizeRange,
+        },
+        conversion::{base64_to_bytes, bytes_to_base64, bytes_to_hex, hex_to_bytes, Base64},
+        encoding::{
+            ascii85_decode, ascii85_encode, autodetect, base32_decode, base32_encode,
+            decimal_decode, Encoding,
+        },
+        error::CryptoError,
+        hamming::hamming_distance,
+        xor::{fixed_xor, xor, xor_with_key, xor_with_repeating_key},
     };
 
     #[test]
@@ -25,6 +32,201 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_challenge_1a() {
+        assert_eq!(hex_to_bytes("00FFa0"), Ok(vec![0x00, 0xff, 0xa0]));
+        assert_eq!(hex_to_bytes("abc"), Err(CryptoError::BadLength(3)));
+        assert_eq!(
+            hex_to_bytes("zz"),
+            Err(CryptoError::InvalidCharacter {
+                position: 0,
+                byte: b'z'
+            })
+        );
+        let err = hex_to_bytes("49 7").unwrap_err();
+        assert_eq!(
+            err,
+            CryptoError::InvalidCharacter {
+                position: 2,
+                byte: b' '
+            }
+        );
+        assert_eq!(err.to_string(), "invalid character ' ' at position 2");
+    }
+
+    #[test]
+    fn test_challenge_1b() {
+        // RFC 4648 section 10
+        let vectors = [
+            ("", ""),
+            ("f", "Zg=="),
+            ("fo", "Zm8="),
+            ("foo", "Zm9v"),
+            ("foob", "Zm9vYg=="),
+            ("fooba", "Zm9vYmE="),
+            ("foobar", "Zm9vYmFy"),
+        ];
+        for (plain, encoded) in vectors {
+            assert_eq!(bytes_to_base64(plain.as_bytes()), encoded);
+            assert_eq!(Base64::STANDARD.strict().decode(encoded), Ok(plain.into()));
+            let unpadded = encoded.trim_end_matches('=');
+            assert_eq!(Base64::STANDARD_NO_PAD.encode(plain.as_bytes()), unpadded);
+            assert_eq!(
+                Base64::STANDARD_NO_PAD.strict().decode(unpadded),
+                Ok(plain.into())
+            );
+            assert_eq!(base64_to_bytes(unpadded), Ok(plain.into()));
+        }
+
+        assert_eq!(Base64::URL_SAFE.encode(&[0xfb, 0xff]), "-_8=");
+        assert_eq!(Base64::URL_SAFE_NO_PAD.decode("-_8"), Ok(vec![0xfb, 0xff]));
+        assert_eq!(
+            base64_to_bytes("-_8="),
+            Err(CryptoError::InvalidCharacter {
+                position: 0,
+                byte: b'-'
+            })
+        );
+
+        // lenient takes whitespace, and stray bits at the end, which strict doesn't
+        assert_eq!(
+            base64_to_bytes("Zm9v\nYmFy\r\n  Zg==\n"),
+            Ok(b"foobarf".to_vec())
+        );
+        assert_eq!(base64_to_bytes("Zh=="), Ok(b"f".to_vec()));
+        let strict = Base64::STANDARD.strict();
+        assert_eq!(
+            strict.decode("Zm9v\nYmFy"),
+            Err(CryptoError::InvalidCharacter {
+                position: 4,
+                byte: b'\n'
+            })
+        );
+        assert_eq!(strict.decode("Zh=="), Err(CryptoError::InvalidPadding));
+        assert_eq!(strict.decode("Zg"), Err(CryptoError::InvalidPadding));
+        assert_eq!(
+            Base64::STANDARD_NO_PAD.strict().decode("Zg=="),
+            Err(CryptoError::InvalidPadding)
+        );
+        assert_eq!(base64_to_bytes("Zg=a"), Err(CryptoError::InvalidPadding));
+        assert_eq!(base64_to_bytes("Zg="), Err(CryptoError::InvalidPadding));
+        assert_eq!(base64_to_bytes("Zm9vY"), Err(CryptoError::BadLength(5)));
+
+        // the challenge files are wrapped at 60 columns
+        let file = read_to_string("src/set1/6.txt").unwrap();
+        let decoded = base64_to_bytes(&file).unwrap();
+        assert_eq!(
+            bytes_to_base64(&decoded),
+            file.split_whitespace().collect::<String>()
+        );
+    }
+
+    #[test]
+    fn test_challenge_1c() {
+        let plain = b"foobar";
+        let vectors = [
+            (Encoding::Hex, "666f6f626172"),
+            (Encoding::Base64, "Zm9vYmFy"),
+            (Encoding::Base64Url, "Zm9vYmFy"),
+            (Encoding::Base32, "MZXW6YTBOI======"),
+            (Encoding::Ascii85, "AoDTs@<)"),
+            (Encoding::Decimal, "[102, 111, 111, 98, 97, 114]"),
+            (Encoding::Raw, "foobar"),
+        ];
+        for (encoding, encoded) in vectors {
+            assert_eq!(encoding.encode(plain), encoded.as_bytes());
+            assert_eq!(encoding.decode(encoded.as_bytes()), Ok(plain.to_vec()));
+            assert_eq!(Encoding::from_name(encoding.name()), Some(encoding));
+        }
+
+        // RFC 4648 section 10
+        let base32 = [
+            "", "MY======", "MZXQ====", "MZXW6===", "MZXW6YQ=", "MZXW6YTB",
+        ];
+        for (i, encoded) in base32.into_iter().enumerate() {
+            assert_eq!(base32_encode(&plain[..i]), encoded);
+            assert_eq!(base32_decode(encoded), Ok(plain[..i].to_vec()));
+            assert_eq!(
+                base32_decode(encoded.trim_end_matches('=')),
+                Ok(plain[..i].to_vec())
+            );
+        }
+        assert_eq!(base32_decode("MZX"), Err(CryptoError::BadLength(3)));
+        assert_eq!(
+            base32_decode("MZ1W6==="),
+            Err(CryptoError::InvalidCharacter {
+                position: 2,
+                byte: b'1'
+            })
+        );
+
+        assert_eq!(ascii85_encode(&[0, 0, 0, 0, 1]), "z!<");
+        assert_eq!(ascii85_decode("<~ z!<\n~>"), Ok(vec![0, 0, 0, 0, 1]));
+        assert_eq!(
+            ascii85_decode("9jqo^BlbD-BleB1DJ+*+F(f,q"),
+            Ok(b"Man is distinguished".to_vec())
+        );
+        assert_eq!(
+            ascii85_decode("uuuuu"),
+            Err(CryptoError::OutOfRange { position: 0 })
+        );
+        assert_eq!(ascii85_decode("9jqo^B"), Err(CryptoError::BadLength(1)));
+        assert_eq!(
+            ascii85_decode("<~ z!<\n"),
+            Err(CryptoError::MissingDelimiter {
+                position: 6,
+                delimiter: "~>"
+            })
+        );
+
+        assert_eq!(decimal_decode("72 105,\n33"), Ok(b"Hi!".to_vec()));
+        assert_eq!(
+            decimal_decode("[72, 256]"),
+            Err(CryptoError::OutOfRange { position: 5 })
+        );
+        let err = decimal_decode(" [72, 105 ").unwrap_err();
+        assert_eq!(
+            err,
+            CryptoError::MissingDelimiter {
+                position: 9,
+                delimiter: "]"
+            }
+        );
+        assert_eq!(err.to_string(), "missing closing ] at position 9");
+        assert_eq!(
+            Encoding::Hex.decode(b"  4g\n"),
+            Err(CryptoError::InvalidCharacter {
+                position: 3,
+                byte: b'g'
+            })
+        );
+
+        let best = |input: &[u8]| autodetect(input)[0].0;
+        assert_eq!(best(b"49276d206b696c6c\n"), Encoding::Hex);
+        assert_eq!(best(b"SSdtIGtpbGxpbmcgeW91cg=="), Encoding::Base64);
+        assert_eq!(best(b"-_8="), Encoding::Base64Url);
+        assert_eq!(best(b"JEQGS3DMNFXGOIDZN52XE==="), Encoding::Base32);
+        assert_eq!(best(b"<~9jqo^BlbD-BleB1DJ+*+F(f,q~>"), Encoding::Ascii85);
+        assert_eq!(best(b"[72, 105]"), Encoding::Decimal);
+        assert_eq!(best(&[0xff, 0x00, 0x10]), Encoding::Raw);
+        // plain words fit base32 and ascii85 too, but they're still plain words
+        assert_eq!(best(b"I'm killing your brain"), Encoding::Raw);
+        assert_eq!(best(b"hello"), Encoding::Raw);
+        assert_eq!(best(b"hello world\n"), Encoding::Raw);
+        assert_eq!(best(b"brain"), Encoding::Raw);
+        // but not once they show characters words don't have
+        assert_eq!(best(b"9jqo^BlbD-BleB1DJ+*+F(f,q"), Encoding::Ascii85);
+        assert_eq!(best(b"jeqgs3dmnfxgoidzn52xe==="), Encoding::Base32);
+        assert!(autodetect(b"not hex!")
+            .iter()
+            .all(|(e, _)| *e != Encoding::Hex));
+
+        // the confidences are in order, and each guess decodes
+        let guesses = autodetect(b"Zm9vYmFy");
+        assert!(guesses.windows(2).all(|w| w[0].1 >= w[1].1));
+        assert!(guesses.iter().all(|(e, _)| e.decode(b"Zm9vYmFy").is_ok()));
+    }
+
     #[test]
     fn test_challenge_2() {
         let a = hex_to_bytes("1c0111001f010100061a024b53535009181c").unwrap();
@@ -34,6 +236,27 @@ mod tests {
         assert_eq!(res, "746865206b696420646f6e277420706c6179");
     }
 
+    #[test]
+    fn test_challenge_2a() {
+        assert_eq!(fixed_xor(&[1, 2], &[3, 4]), Ok(vec![2, 6]));
+        let err = fixed_xor(&[1, 2], &[3]).unwrap_err();
+        assert_eq!(
+            err,
+            CryptoError::LengthMismatch {
+                expected: 2,
+                actual: 1
+            }
+        );
+        assert_eq!(err.to_string(), "expected length 2, got 1");
+        assert_eq!(
+            xor_with_repeating_key(&[1, 2], &[]),
+            Err(CryptoError::InvalidKeyLength(0))
+        );
+
+        let err: Box<dyn std::error::Error> = Box::new(CryptoError::OracleRejection);
+        assert_eq!(err.to_string(), "rejected by the oracle");
+    }
+
     #[test]
     fn test_challenge_3() {
         let t = hex_to_bytes("5468697320697320746865206c6173742074696d6520746861742049276d20657665722077616c6b696e6720686f6d6520616761696e0d0a").unwrap();
@@ -61,7 +284,7 @@ mod tests {
     fn test_challenge_5() {
         let a = "Burning 'em, if you ain't quick and nimble\nI go crazy when I hear a cymbal"
             .as_bytes();
-        let modified = xor_with_repeating_key(&a, "ICE".as_bytes());
+        let modified = xor_with_repeating_key(&a, "ICE".as_bytes()).unwrap();
         let res = bytes_to_hex(modified);
         assert_eq!(
             res,
@@ -76,57 +299,30 @@ mod tests {
 
     #[test]
     fn test_challenge_6b() {
-        let decoded = general_purpose::STANDARD
-            .decode(&read_to_string("src/set1/6.txt").unwrap().replace("\n", ""))
-            .unwrap();
-
-        // find keysize.
-        let (key_sz, _key_dist) = (2..=40)
-            .map(|keysize| {
-                let mut distance = 0;
-                let mut i = 0;
-                while (i + 2) * keysize < decoded.len() {
-                    distance += hamming_distance_bytes(
-                        &decoded[i * keysize..(i + 1) * keysize],
-                        &decoded[(i + 1) * keysize..(i + 2) * keysize],
-                    );
-                    i += 2;
-                }
-
-                let normalized_distance =
-                    f64::from(distance) / f64::from(u32::try_from(i / 2 * keysize).unwrap());
-                (keysize, normalized_distance)
-            })
-            .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap())
-            .unwrap();
-
-        println!("{:?}", key_sz);
+        let decoded = base64_to_bytes(read_to_string("src/set1/6.txt").unwrap()).unwrap();
 
-        let key = (0..key_sz)
-            .map(|offset| {
-                let transposed = transpose(&decoded, key_sz, offset);
-                let best = freq_analysis_iter(&transposed)
-                    .min_by(|a: &(f64, u8, String), b| a.0.partial_cmp(&b.0).unwrap());
+        let candidates = break_repeating_xor(&decoded, KeySizeRange::default());
+        assert!(!candidates.is_empty() && candidates.len() <= 3);
+        assert!(candidates.windows(2).all(|w| w[0].score <= w[1].score));
 
-                match best {
-                    Some((_, k, _)) => return Some(k),
-                    None => return None,
-                }
-            })
-            .filter(|k| k.is_some())
-            .map(|f| f.unwrap() as char);
+        let best = &candidates[0];
+        assert_eq!(best.key, b"Terminator X: Bring the noise");
+        assert!(best
+            .plaintext
+            .starts_with(b"I'm back and I'm ringin' the bell"));
 
-        println!("{:?}", String::from_iter(key));
+        // the right size is closest among all the pairs of blocks
+        let distance = |size| key_size_distance(&decoded, size).unwrap();
+        assert!((2..=40).all(|size| distance(29) <= distance(size)));
+        assert_eq!(key_size_distance(&decoded[..40], 29), None);
     }
 
     #[test]
     fn test_challenge_7() {
         let key = "YELLOW SUBMARINE".as_bytes();
-        let decoded = general_purpose::STANDARD
-            .decode(&read_to_string("src/set1/7.txt").unwrap().replace("\n", ""))
-            .unwrap();
+        let decoded = base64_to_bytes(read_to_string("src/set1/7.txt").unwrap()).unwrap();
 
-        let full = String::from_utf8(decrypt_ecb(key, decoded)).unwrap();
+        let full = String::from_utf8(decrypt_ecb(key, decoded).unwrap()).unwrap();
 
         print!("{}", full);
     }
@@ -2,14 +2,16 @@
 mod tests {
     use std::fs::read_to_string;
 
-    use base64::{engine::general_purpose, Engine};
     use rand::Rng;
 
     use crate::shared::{
         aes::{
-            decrypt_cbc, decrypt_ecb, detect_block_and_suffix_size, detect_ebc, encrypt_ecb,
-            get_encryption_oracle, get_encryption_oracle_with_suffix, pkcs7_padding,
+            decrypt_cbc, decrypt_ecb, detect_block_and_suffix_size, detect_ebc, encrypt_cbc,
+            encrypt_ecb, get_encryption_oracle, get_encryption_oracle_with_suffix, pkcs7_padding,
+            pkcs7_unpad,
         },
+        conversion::base64_to_bytes,
+        error::CryptoError,
         kv::profile_for,
     };
 
@@ -18,9 +20,9 @@ mod tests {
         let key = "YELLOW SUBMARINE".as_bytes();
         let v = "YELLOW SUBMARINE".as_bytes();
 
-        let e = encrypt_ecb(key, v.to_vec());
+        let e = encrypt_ecb(key, v.to_vec()).unwrap();
 
-        let d = String::from_utf8(decrypt_ecb(key, e)).unwrap();
+        let d = String::from_utf8(decrypt_ecb(key, e).unwrap()).unwrap();
 
         assert_eq!(d, "YELLOW SUBMARINE");
     }
@@ -49,16 +51,46 @@ mod tests {
         assert_eq!(padded.len(), 32 as usize);
     }
 
+    #[test]
+    fn test_challenge_1c() {
+        let padded = pkcs7_padding(b"ICE ICE BABY", 16);
+        assert_eq!(pkcs7_unpad(&padded, 16), Ok(b"ICE ICE BABY".to_vec()));
+        assert_eq!(
+            pkcs7_unpad(b"ICE ICE BABY\x05\x05\x05\x05", 16),
+            Err(CryptoError::InvalidPadding)
+        );
+        assert_eq!(
+            pkcs7_unpad(b"ICE ICE BABY\x01\x02\x03\x04", 16),
+            Err(CryptoError::InvalidPadding)
+        );
+        assert_eq!(
+            pkcs7_unpad(b"ICE ICE BABY\x00\x00\x00\x00", 16),
+            Err(CryptoError::InvalidPadding)
+        );
+        assert_eq!(pkcs7_unpad(b"ICE", 16), Err(CryptoError::BadLength(3)));
+
+        assert_eq!(
+            encrypt_ecb(b"YELLOW", padded.clone()),
+            Err(CryptoError::InvalidKeyLength(6))
+        );
+        assert_eq!(
+            decrypt_ecb(b"YELLOW SUBMARINE", padded[..15].to_vec()),
+            Err(CryptoError::BadLength(15))
+        );
+        assert_eq!(
+            encrypt_cbc(b"YELLOW SUBMARINE", padded, &[0; 8]),
+            Err(CryptoError::InvalidIvLength(8))
+        );
+    }
+
     #[test]
     fn test_challenge_2() {
         let key = "YELLOW SUBMARINE".as_bytes();
-        let decoded = general_purpose::STANDARD
-            .decode(&read_to_string("src/set2/10.txt").unwrap().replace("\n", ""))
-            .unwrap();
+        let decoded = base64_to_bytes(read_to_string("src/set2/10.txt").unwrap()).unwrap();
 
         let iv = vec![0; 16];
 
-        let d = decrypt_cbc(key, decoded, &iv);
+        let d = decrypt_cbc(key, decoded, &iv).unwrap();
         println!("{}", String::from_utf8(d).unwrap());
     }
 
@@ -154,13 +186,13 @@ mod tests {
         let f = profile_for(&input);
         let profile = f.as_bytes();
 
-        let e = encrypt_ecb(&key, pkcs7_padding(&profile, 16));
+        let e = encrypt_ecb(&key, pkcs7_padding(&profile, 16)).unwrap();
         println!("{:?}", e.len());
 
         // swap blocks 2 & 3, drop block 4.
         let mix: Vec<u8> = [e[0..16].to_vec(), e[32..48].to_vec(), e[16..32].to_vec()].concat();
 
-        let dec = decrypt_ecb(&key, mix);
+        let dec = decrypt_ecb(&key, mix).unwrap();
 
         println!("{:?}", String::from_utf8(dec.clone()).unwrap());
     }
@@ -0,0 +1,169 @@
+#[cfg(test)]
+mod tests {
+    use num_bigint::BigUint;
+    use pretty_assertions::assert_eq;
+
+    use crate::shared::{
+        bignum::biguint_to_bytes,
+        bleichenbacher::{
+            bleichenbacher_attack, get_padding_oracle, is_conforming, OracleStrictness,
+        },
+        conversion::base64_to_bytes,
+        rsa::{
+            decrypt, encrypt, generate_keypair, get_lsb_oracle, get_parity_oracle, key_len,
+            lsb_attack, parity_attack, pkcs1_pad, pkcs1_unpad,
+        },
+    };
+
+    const CHALLENGE_46: &str = "VGhhdCdzIHdoeSBJIGZvdW5kIHlvdSBkb24ndCBwbGF5IGFyb3VuZCB3aXRoIHRoZSBGdW5reSBDb2xkIE1lZGluYQ==";
+
+    #[test]
+    fn test_challenge_6a() {
+        let (public, private) = generate_keypair(1024, 65537);
+        let plain_text = base64_to_bytes(CHALLENGE_46).unwrap();
+        let ct = encrypt(&public, &BigUint::from_bytes_be(&plain_text));
+
+        let oracle = get_parity_oracle(private);
+        let recovered = parity_attack(&public, &ct, oracle, |upper| {
+            // hollywood style, the top bytes settle first
+            println!("{}", String::from_utf8_lossy(&upper.to_bytes_be()));
+        });
+
+        assert_eq!(recovered.to_bytes_be(), plain_text);
+    }
+
+    #[test]
+    fn test_challenge_6b() {
+        // leaking more bits per query takes proportionally fewer queries
+        let (public, private) = generate_keypair(1024, 65537);
+        let plain_text = base64_to_bytes(CHALLENGE_46).unwrap();
+        let ct = encrypt(&public, &BigUint::from_bytes_be(&plain_text));
+
+        for k in [3, 8] {
+            let oracle = get_lsb_oracle(private.clone(), k);
+            let mut steps = 0;
+            let recovered = lsb_attack(&public, &ct, k, oracle, |_| steps += 1);
+
+            assert_eq!(recovered.to_bytes_be(), plain_text);
+            assert_eq!(steps, 1024_u64.div_ceil(u64::from(k)));
+        }
+    }
+
+    #[test]
+    fn test_challenge_7() {
+        let (public, private) = generate_keypair(256, 3);
+        let k = key_len(&public.n);
+
+        let em = pkcs1_pad("kick it, CC".as_bytes(), k);
+        let ct = encrypt(&public, &BigUint::from_bytes_be(&em));
+        assert_eq!(
+            pkcs1_unpad(&biguint_to_bytes(&decrypt(&private, &ct), k)).unwrap(),
+            "kick it, CC".as_bytes()
+        );
+
+        let oracle = get_padding_oracle(private, OracleStrictness::TTT, 0);
+        let result = bleichenbacher_attack(&public, &ct, oracle);
+        println!("recovered in {} queries", result.queries);
+
+        assert_eq!(result.plain_text, em);
+        assert_eq!(
+            pkcs1_unpad(&result.plain_text).unwrap(),
+            "kick it, CC".as_bytes()
+        );
+    }
+
+    #[test]
+    fn test_challenge_8() {
+        let (public, private) = generate_keypair(768, 3);
+        let k = key_len(&public.n);
+        let message = "I was lost, but now I'm found".as_bytes();
+
+        let em = pkcs1_pad(message, k);
+        let ct = encrypt(&public, &BigUint::from_bytes_be(&em));
+
+        let oracle = get_padding_oracle(private, OracleStrictness::TTT, 0);
+        let result = bleichenbacher_attack(&public, &ct, oracle);
+        println!("recovered in {} queries", result.queries);
+
+        assert_eq!(pkcs1_unpad(&result.plain_text).unwrap(), message);
+    }
+
+    #[test]
+    fn test_challenge_8a() {
+        let em = |separator: usize| {
+            let mut em = vec![0, 2];
+            em.extend(vec![0xff; separator - 2]);
+            em.push(0);
+            em.extend(vec![0x41; 31 - separator]);
+            em
+        };
+
+        // a 16 byte message in a 32 byte block
+        assert!(is_conforming(&em(15), OracleStrictness::FFF, 16));
+        assert!(!is_conforming(&em(16), OracleStrictness::FFF, 16));
+        assert!(is_conforming(&em(16), OracleStrictness::FFT, 16));
+        assert!(!is_conforming(&em(5), OracleStrictness::FFT, 16));
+        assert!(is_conforming(&em(5), OracleStrictness::TTT, 16));
+        assert!(!is_conforming(
+            &[[0, 2].as_slice(), &[1; 30]].concat(),
+            OracleStrictness::FFT,
+            16
+        ));
+        assert!(is_conforming(
+            &[[0, 2].as_slice(), &[1; 30]].concat(),
+            OracleStrictness::TTT,
+            16
+        ));
+    }
+
+    // stricter oracles answer true less often, so cost more queries. A modulus one
+    // bit into its top byte makes 00 02 blocks 1 in 2^9 rather than 1 in 2^16, which
+    // keeps FFT to a second or two. Summed over a few keys, so that one lucky run
+    // can't decide it.
+    #[test]
+    fn test_challenge_8b() {
+        let message = "kick it, CC".as_bytes();
+        let mut queries = [0, 0];
+        for _ in 0..3 {
+            let (public, private) = generate_keypair(257, 3);
+            let k = key_len(&public.n);
+            let ct = encrypt(&public, &BigUint::from_bytes_be(&pkcs1_pad(message, k)));
+
+            for (total, strictness) in queries
+                .iter_mut()
+                .zip([OracleStrictness::TTT, OracleStrictness::FFT])
+            {
+                let oracle = get_padding_oracle(private.clone(), strictness, message.len());
+                let result = bleichenbacher_attack(&public, &ct, oracle);
+                assert_eq!(pkcs1_unpad(&result.plain_text).unwrap(), message);
+                *total += result.queries;
+            }
+        }
+        println!("TTT: {} queries, FFT: {} queries", queries[0], queries[1]);
+
+        assert!(queries[0] < queries[1]);
+    }
+
+    // FFF also wants the message length right, and takes hundreds of thousands of
+    // queries even so, so this only runs with `--ignored`
+    #[test]
+    #[ignore]
+    fn test_challenge_8c() {
+        let (public, private) = generate_keypair(257, 3);
+        let k = key_len(&public.n);
+        let message = "kick it, CC".as_bytes();
+        let ct = encrypt(&public, &BigUint::from_bytes_be(&pkcs1_pad(message, k)));
+
+        for strictness in [
+            OracleStrictness::TTT,
+            OracleStrictness::FFT,
+            OracleStrictness::FFF,
+        ] {
+            let oracle = get_padding_oracle(private.clone(), strictness, message.len());
+            let result = bleichenbacher_attack(&public, &ct, oracle);
+            println!("{:?}: {} queries", strictness, result.queries);
+
+            assert_eq!(pkcs1_unpad(&result.plain_text).unwrap(), message);
+        }
+    }
+}
@@ -0,0 +1,364 @@
+#[cfg(test)]
+mod tests {
+    use std::{env::temp_dir, fs::remove_file, thread::available_parallelism};
+
+    use pretty_assertions::assert_eq;
+    use rand::Rng;
+
+    use crate::shared::{
+        bank::{forge_extension, forge_sender, Server, Transfer},
+        conversion::bytes_to_hex,
+        crime::{get_compression_oracle, recover_secret, Mode},
+        error::CryptoError,
+        herding::Diamond,
+        joux::{cascade_collision, multicollision},
+        mac::{cbc_mac, cbc_mac_collide, cbc_mac_hash},
+        md::MdHash,
+        md4::md4,
+        rc4::{
+            decrypt_rc4, encrypt_rc4, get_cookie_oracle, get_rc4_oracle, keystream_statistics,
+            recover_rc4_cookie,
+        },
+        second_preimage::second_preimage,
+        wang::{enforced_conditions, find_md4_collision},
+    };
+
+    #[test]
+    fn test_challenge_1a() {
+        let server = Server::new();
+        let attacker = server.client(3);
+
+        // the attacker sends money to themselves, then rewrites who it was from
+        let request = attacker.transfer(3, 1000000);
+        assert_eq!(
+            server.process(&request),
+            Ok(Transfer {
+                from: 3,
+                to: 3,
+                amount: 1000000
+            })
+        );
+
+        let forged = forge_sender(&request, 7);
+        assert_eq!(
+            server.process(&forged),
+            Ok(Transfer {
+                from: 7,
+                to: 3,
+                amount: 1000000
+            })
+        );
+
+        // the MAC only covers the message, not a sender rewritten in plain sight
+        let mut tampered = request.clone();
+        tampered[5] = b'7';
+        assert_eq!(server.process(&tampered), Err(CryptoError::OracleRejection));
+        assert_eq!(
+            server.process(&request[..20]),
+            Err(CryptoError::BadLength(20))
+        );
+        assert_eq!(
+            server.process_many(&request),
+            Err(CryptoError::OracleRejection)
+        );
+    }
+
+    #[test]
+    fn test_challenge_1b() {
+        let server = Server::new();
+        let victim = server.client(7);
+
+        // ids of one and two digits, with the header ending in and at the first block
+        for account in [3, 42] {
+            let attacker = server.client(account);
+
+            // keep sniffing the victim's requests until the glue block is usable
+            let forged = (1..)
+                .map(|amount| victim.transfer_many(&[(5, amount), (6, 250)]))
+                .find_map(|request| forge_extension(&request, &attacker, 1000000).ok())
+                .unwrap();
+
+            let transfers = server.process_many(&forged).unwrap();
+            assert_eq!(transfers[0].from, 7);
+            assert_eq!(
+                transfers.last(),
+                Some(&Transfer {
+                    from: 7,
+                    to: account,
+                    amount: 1000000
+                })
+            );
+        }
+
+        // `from=100&tx_list=` runs past the block the glue garbles
+        let request = victim.transfer_many(&[(5, 100)]);
+        assert_eq!(
+            forge_extension(&request, &server.client(100), 1000000),
+            Err(CryptoError::BadLength(17))
+        );
+    }
+
+    #[test]
+    fn test_challenge_2() {
+        let snippet = "alert('MZA who was that?');\n".as_bytes();
+        let target = cbc_mac_hash(snippet);
+        assert_eq!(
+            bytes_to_hex(target.clone()),
+            "296b8d7cb78a243dda4d0a61d33bbdd1"
+        );
+
+        // the padding and glue block hide behind a `//` comment, so neither can contain
+        // a line break. pad the prefix out with spaces until that holds
+        let suffix = "\n// MZA who was that?".as_bytes();
+        let forged = (0..)
+            .map(|spaces| {
+                let prefix = format!("alert('Ayo, the Wu is back!');{}//", " ".repeat(spaces));
+                cbc_mac_collide(&target, prefix.as_bytes(), suffix).unwrap()
+            })
+            .find(|forged| {
+                !forged[..forged.len() - suffix.len()]
+                    .iter()
+                    .any(|b| b"\r\n".contains(b))
+            })
+            .unwrap();
+        println!("{}", String::from_utf8_lossy(&forged));
+
+        assert_ne!(forged, snippet);
+        assert!(forged.starts_with("alert('Ayo, the Wu is back!');".as_bytes()));
+        assert!(forged.ends_with(suffix));
+        assert_eq!(cbc_mac_hash(&forged), target);
+
+        assert_eq!(
+            cbc_mac(b"YELLOW", snippet, &[0; 16]),
+            Err(CryptoError::InvalidKeyLength(6))
+        );
+        assert_eq!(
+            cbc_mac(b"YELLOW SUBMARINE", snippet, &[0; 8]),
+            Err(CryptoError::InvalidIvLength(8))
+        );
+        assert_eq!(
+            cbc_mac_collide(&target[..8], b"", suffix),
+            Err(CryptoError::BadLength(8))
+        );
+    }
+
+    #[test]
+    fn test_challenge_3() {
+        let session_id = "TmV2ZXIgcmV2ZWFsIHRoZSBXdS1UYW5nIFNlY3JldCE=";
+
+        for mode in [Mode::Ctr, Mode::Cbc] {
+            let oracle = get_compression_oracle(mode, session_id);
+            let result = recover_secret(oracle, "sessionid=");
+            println!("{:?}: {:?}", mode, result);
+
+            assert_eq!(result.secret, session_id);
+            assert!(result.tied.is_empty());
+        }
+
+        // an oracle that gives nothing away leaves every candidate tied, and nothing
+        // guessed
+        let result = recover_secret(|_| 100, "sessionid=");
+        assert_eq!(result.secret, "");
+        assert!(result.tied.len() > 1);
+    }
+
+    #[test]
+    fn test_challenge_4() {
+        let f = MdHash::new(16);
+        let g = MdHash::new(32);
+
+        let collision = multicollision(&f, f.iv, 4);
+        let messages = collision.messages().collect::<Vec<Vec<u8>>>();
+        assert_eq!(messages.len(), 16);
+        assert!(messages.iter().all(|m| f.hash(m) == f.hash(&messages[0])));
+
+        let result = cascade_collision(&f, &g);
+        println!(
+            "{} colliding messages in f, {} calls to f, {} calls to g",
+            result.multicollision_size, result.f_calls, result.g_calls
+        );
+
+        let [a, b] = &result.messages;
+        assert_ne!(a, b);
+        assert_eq!(f.hash(a), f.hash(b));
+        assert_eq!(g.hash(a), g.hash(b));
+    }
+
+    #[test]
+    fn test_challenge_5() {
+        let hash = MdHash::new(24);
+        let mut rng = rand::thread_rng();
+
+        // a longer target is cheaper to hit, at the cost of a longer expandable message
+        for k in [6, 8, 10] {
+            let target: Vec<u8> = (0..(1 << k) * 16).map(|_| rng.gen()).collect();
+            let result = second_preimage(&hash, &target, k);
+            println!(
+                "k = {}: {} calls for the expandable message, {} for the bridge",
+                k, result.expandable_calls, result.bridge_calls
+            );
+
+            assert_ne!(result.message, target);
+            assert_eq!(result.message.len(), target.len());
+            assert_eq!(hash.hash(&result.message), hash.hash(&target));
+        }
+    }
+
+    #[test]
+    fn test_challenge_6() {
+        let hash = MdHash::new(24);
+
+        // built once and stored, then reused for as many predictions as we like
+        let path = temp_dir().join("cryptopals-diamond-24-8.bin");
+        let diamond = Diamond::build(&hash, 8);
+        diamond.save(&path).unwrap();
+        let diamond = Diamond::load(&path).unwrap();
+        remove_file(&path).unwrap();
+
+        let prediction = diamond.prediction(4);
+        println!("predicted hash: {:06x}", prediction);
+
+        // ... the season is played out
+        let scores = "Cubs 7, Mets 3; Yankees 2, Red Sox 11; Giants 4, Dodgers 5";
+        let (message, calls) = diamond.link(scores.as_bytes(), 4).unwrap();
+        println!(
+            "linked in {} calls: {:?}",
+            calls,
+            String::from_utf8_lossy(&message)
+        );
+
+        assert!(message.starts_with(scores.as_bytes()));
+        assert_eq!(hash.hash(&message), prediction);
+    }
+
+    #[test]
+    fn test_challenge_6a() {
+        let hash = MdHash::new(16);
+        let diamond = Diamond::build(&hash, 4);
+
+        // the prefix pads to 2 blocks on its own, but 6 were promised
+        let prediction = diamond.prediction(6);
+        let scores = "Cubs 7, Mets 3; Yanks";
+        let (message, _) = diamond.link(scores.as_bytes(), 6).unwrap();
+        assert!(message.starts_with(scores.as_bytes()));
+        assert_eq!(message.len(), (6 + 1 + 4) * 16);
+        assert_eq!(hash.hash(&message), prediction);
+
+        let (message, _) = diamond.link(&[b'x'; 96], 6).unwrap();
+        assert_eq!(hash.hash(&message), prediction);
+        assert_eq!(
+            diamond.link(&[b'x'; 97], 6),
+            Err(CryptoError::BadLength(97))
+        );
+
+        // a stored diamond has to fit the hash it was built for
+        let bytes = diamond.to_bytes();
+        assert_eq!(Diamond::from_bytes(&bytes).as_ref(), Ok(&diamond));
+        let len = bytes.len();
+        assert_eq!(
+            Diamond::from_bytes(&bytes[..len - 1]),
+            Err(CryptoError::BadLength(len - 1))
+        );
+        assert_eq!(
+            Diamond::from_bytes(&[bytes.as_slice(), &[0]].concat()),
+            Err(CryptoError::BadLength(len + 1))
+        );
+        let mut wide = bytes.clone();
+        wide[4..12].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert_eq!(
+            Diamond::from_bytes(&wide),
+            Err(CryptoError::OutOfRange { position: 4 })
+        );
+        let mut wide = bytes.clone();
+        wide[16 + 2] = 1;
+        assert_eq!(
+            Diamond::from_bytes(&wide),
+            Err(CryptoError::OutOfRange { position: 16 })
+        );
+    }
+
+    #[test]
+    fn test_challenge_7a() {
+        assert_eq!(bytes_to_hex(md4(b"")), "31d6cfe0d16ae931b73c59d7e0c089c0");
+        assert_eq!(
+            bytes_to_hex(md4(b"abc")),
+            "a448017aaf21d8525fc10ae87aa6729d"
+        );
+        assert_eq!(
+            bytes_to_hex(md4(
+                b"12345678901234567890123456789012345678901234567890123456789012345678901234567890"
+            )),
+            "e33b4ddc9c38f2199c3e7b164fcc0536"
+        );
+    }
+
+    #[test]
+    fn test_challenge_7b() {
+        let result = find_md4_collision();
+        println!(
+            "{} conditions enforced, collision after {} attempts ({} met every enforced condition, {} every condition)",
+            enforced_conditions().len(),
+            result.attempts,
+            result.enforced_held,
+            result.all_held
+        );
+
+        let [m, m_prime] = &result.messages;
+        println!(
+            "{}\n{}",
+            bytes_to_hex(m.clone()),
+            bytes_to_hex(m_prime.clone())
+        );
+
+        assert_ne!(m, m_prime);
+        assert_eq!(md4(m), md4(m_prime));
+    }
+
+    #[test]
+    fn test_challenge_8a() {
+        let encrypted = encrypt_rc4(b"Key", b"Plaintext");
+        assert_eq!(bytes_to_hex(encrypted.clone()), "bbf316e8d940af0ad3");
+        assert_eq!(decrypt_rc4(b"Key", &encrypted), b"Plaintext");
+
+        assert_eq!(bytes_to_hex(encrypt_rc4(b"Wiki", b"pedia")), "1021bf0420");
+    }
+
+    #[test]
+    fn test_challenge_8b() {
+        // 2^22 samples was not always enough to pick the bias out of the noise
+        let threads = available_parallelism().map_or(4, |n| n.get());
+        let counts = keystream_statistics(32, 1 << 23, threads);
+
+        let most_common = |position: usize| {
+            counts[position]
+                .iter()
+                .enumerate()
+                .max_by_key(|(_, count)| **count)
+                .unwrap()
+                .0
+        };
+        assert_eq!(most_common(15), 240);
+        assert_eq!(most_common(31), 224);
+    }
+
+    // a two byte cookie only needs the paddings that put it under Z16, and the
+    // 2^23 samples that were enough to pick that bias out in 8b
+    #[test]
+    fn test_challenge_8c() {
+        let threads = available_parallelism().map_or(4, |n| n.get());
+        let recovered = recover_rc4_cookie(get_rc4_oracle(b"OK"), 2, 1 << 23, threads);
+
+        assert_eq!(recovered, b"OK");
+    }
+
+    // 2^24 samples for each of 16 paddings, so only runs with `--ignored`
+    #[test]
+    #[ignore]
+    fn test_challenge_8d() {
+        let threads = available_parallelism().map_or(4, |n| n.get());
+        let recovered = recover_rc4_cookie(get_cookie_oracle(), 30, 1 << 24, threads);
+        println!("{}", String::from_utf8_lossy(&recovered));
+
+        assert_eq!(recovered, "BE SURE TO DRINK YOUR OVALTINE".as_bytes());
+    }
+}
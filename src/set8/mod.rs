@@ -0,0 +1,741 @@
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use num_bigint::{BigInt, BigUint, RandBigInt};
+    use num_rational::BigRational;
+    use num_traits::Zero;
+
+    use crate::shared::{
+        bignum::small_factors,
+        conversion::{bytes_to_hex, hex_to_bytes},
+        dh::{generate_keypair, get_mac_oracle, shared_secret, DhParams},
+        dlog::{bsgs, dlog_interval, kangaroo, Group, Jumps, ModP},
+        dsks::{ecdsa_dsks, rsa_dsks},
+        ec::{self, get_ecdh_mac_oracle, invalid_curves, EcParams, Point},
+        ecdsa,
+        error::CryptoError,
+        fault::{fault_attack, get_handshake_oracle, FaultModel},
+        forbidden::{auth_key_candidates, forge_tag, GcmMessage},
+        gcm::{auth_key, decrypt_gcm, encrypt_gcm, ghash},
+        gf128::Gf128,
+        gf2::{multiplication_matrix, squaring_matrix, Gf2Matrix, Gf2Vector},
+        hnp::{get_biased_signing_oracle, hnp_attack},
+        invalid_curve::invalid_curve_attack,
+        lattice::{dot, from_integers, gram_schmidt, lll, Vector},
+        mac::hmac_sha256,
+        montgomery::{get_ladder_mac_oracle, MontgomeryCurve},
+        polynomial::{
+            distinct_degree_factorization, equal_degree_factorization, roots,
+            square_free_factorization, Polynomial,
+        },
+        rsa,
+        sha256::sha256,
+        subgroup::{small_subgroup_attack, subgroup_kangaroo_attack},
+        truncated_mac::{ferguson_attack, get_truncated_gcm_oracle},
+        twist::{random_twist_u, twist_attack, twist_attack_in, twist_order, twist_residues},
+    };
+
+    fn parse(n: &str) -> BigUint {
+        BigUint::parse_bytes(n.as_bytes(), 10).unwrap()
+    }
+
+    #[test]
+    fn test_challenge_1a() {
+        assert_eq!(
+            bytes_to_hex(sha256(b"")),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            bytes_to_hex(sha256(
+                b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"
+            )),
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+        );
+
+        // RFC 4231 test cases 2 and 6, the second with a key longer than a block
+        assert_eq!(
+            bytes_to_hex(hmac_sha256(b"Jefe", b"what do ya want for nothing?")),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        assert_eq!(
+            bytes_to_hex(hmac_sha256(
+                &[0xaa; 131],
+                b"Test Using Larger Than Block-Size Key - Hash Key First"
+            )),
+            "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54"
+        );
+    }
+
+    #[test]
+    fn test_challenge_1b() {
+        let params = DhParams::challenge_57();
+        let j = (&params.p - 1u32) / &params.q;
+        assert_eq!(
+            small_factors(&j, 1 << 16),
+            [2, 3, 5, 109, 7963, 8539, 20641, 38833, 39341, 46337, 51977, 54319, 57529]
+        );
+
+        let (alice_secret, alice_public) = generate_keypair(&params);
+        let (bob_secret, bob_public) = generate_keypair(&params);
+        assert_eq!(
+            shared_secret(&params, &alice_secret, &bob_public),
+            shared_secret(&params, &bob_secret, &alice_public)
+        );
+
+        let oracle = get_mac_oracle(
+            params.clone(),
+            bob_secret.clone(),
+            b"crazy flamboyant for the rap enjoyment",
+        );
+        let result = small_subgroup_attack(&params, 1 << 16, oracle);
+        println!("recovered in {} queries", result.queries);
+
+        assert!(result.modulus > params.q);
+        assert_eq!(result.residue, bob_secret);
+    }
+
+    #[test]
+    fn test_challenge_2a() {
+        let DhParams { p, g, .. } = DhParams::challenge_58();
+        let y = parse("7760073848032689505395005705677365876654629189298052775754597607446617558600394076764814236081991643094239886772481052254010323780165093955236429914607119");
+        let (a, b) = (BigUint::from(0u32), BigUint::from(1u32 << 20));
+
+        let x = dlog_interval(&g, &y, &a, &b, &p).unwrap();
+        assert_eq!(g.modpow(&x, &p), y);
+        println!("x = {}", x);
+
+        // the two agree, and any jump function that finds it gives the same answer
+        assert_eq!(bsgs(&g, &y, &a, &b, &p), Some(x.clone()));
+        for k in [8, 10, 12] {
+            if let Some(found) = kangaroo(&g, &y, &a, &b, &p, &Jumps::powers_of_two(k)) {
+                assert_eq!(found, x);
+            }
+        }
+
+        let y = parse("9388897478013399550694114614498790691034187453089355259602614074132918843899833277397448144245883225611726912025846772975325932794909655215329941809013733");
+        let x = dlog_interval(&g, &y, &a, &BigUint::from(1u64 << 40), &p).unwrap();
+        assert_eq!(g.modpow(&x, &p), y);
+        println!("x = {}", x);
+    }
+
+    #[test]
+    fn test_challenge_2b() {
+        let params = DhParams::challenge_58();
+        let (bob_secret, bob_public) = generate_keypair(&params);
+        let oracle = get_mac_oracle(
+            params.clone(),
+            bob_secret.clone(),
+            b"crazy flamboyant for the rap enjoyment",
+        );
+
+        // the small factors alone only cover 89 of the 128 bits
+        let leaked = small_subgroup_attack(&params, 1 << 16, &oracle);
+        assert!(leaked.modulus < params.q);
+        assert_eq!(&bob_secret % &leaked.modulus, leaked.residue);
+
+        let recovered = subgroup_kangaroo_attack(&params, &bob_public, 1 << 16, &oracle);
+        assert_eq!(recovered, Some(bob_secret));
+    }
+
+    #[test]
+    fn test_challenge_3a() {
+        let params = EcParams::cryptopals();
+        let curve = &params.curve;
+        assert!(curve.contains(&params.g));
+        assert_eq!(curve.scalar_mul(&params.g, &params.q), Point::Infinity);
+
+        // the projective and affine arithmetic agree
+        let k = parse("123456789012345678901234567890");
+        let point = curve.scalar_mul(&params.g, &k);
+        assert!(curve.contains(&point));
+        assert_eq!(point, curve.scalar_mul_affine(&params.g, &k));
+        assert_eq!(curve.add(&point, &curve.negate(&point)), Point::Infinity);
+
+        // the whole curve has order 8q
+        let random = curve.random_point();
+        assert!(curve.contains(&random));
+        assert_eq!(
+            curve.scalar_mul(&random, &(&params.q * 8u32)),
+            Point::Infinity
+        );
+
+        let (alice_secret, alice_public) = ec::generate_keypair(&params);
+        let (bob_secret, bob_public) = ec::generate_keypair(&params);
+        assert_eq!(
+            ec::shared_secret(&params, &alice_secret, &bob_public),
+            ec::shared_secret(&params, &bob_secret, &alice_public)
+        );
+    }
+
+    #[test]
+    fn test_challenge_3b() {
+        let params = EcParams::cryptopals();
+        let (bob_secret, _) = ec::generate_keypair(&params);
+        let oracle = get_ecdh_mac_oracle(
+            params.clone(),
+            bob_secret.clone(),
+            b"crazy flamboyant for the rap enjoyment",
+        );
+
+        let curves = invalid_curves(&params);
+        assert!(curves.iter().all(
+            |(curve, order)| curve.scalar_mul(&curve.random_point(), order) == Point::Infinity
+        ));
+
+        let result = invalid_curve_attack(&params, &curves, 1 << 16, oracle);
+        println!("recovered in {} queries", result.queries);
+
+        assert!(result.modulus > params.q);
+        assert_eq!(result.residue, bob_secret);
+    }
+
+    #[test]
+    fn test_challenge_4a() {
+        let params = EcParams::cryptopals();
+        let curve = MontgomeryCurve::cryptopals();
+        assert_eq!(curve.weierstrass(), params.curve);
+
+        let base = Point::Affine(
+            BigUint::from(4u32),
+            parse("85518893674295321206118380980485522083"),
+        );
+        assert_eq!(curve.to_weierstrass(&base), params.g);
+        assert_eq!(curve.from_weierstrass(&params.g), base);
+
+        // the ladder agrees with the Weierstrass arithmetic, up to the change of form
+        let k = parse("123456789012345678901234567890");
+        let Point::Affine(u, _) = curve.from_weierstrass(&params.curve.scalar_mul(&params.g, &k))
+        else {
+            panic!("k is less than q");
+        };
+        assert_eq!(curve.ladder(&BigUint::from(4u32), &k), u);
+        assert_eq!(
+            curve.ladder(&BigUint::from(4u32), &params.q),
+            BigUint::from(0u32)
+        );
+
+        // the twist takes up the rest of the 2p + 2 points
+        let order = &params.q * 8u32;
+        let twist = twist_order(&curve.p, &order);
+        assert_eq!(
+            curve.ladder(&random_twist_u(&curve), &twist),
+            BigUint::from(0u32)
+        );
+    }
+
+    #[test]
+    fn test_challenge_4b() {
+        let params = EcParams::cryptopals();
+        let curve = MontgomeryCurve::cryptopals();
+        let twist = twist_order(&curve.p, &(&params.q * 8u32));
+
+        let (bob_secret, _) = ec::generate_keypair(&params);
+        let oracle = get_ladder_mac_oracle(
+            curve.clone(),
+            bob_secret.clone(),
+            b"crazy flamboyant for the rap enjoyment",
+        );
+
+        // 11, 107, 197 and 1621, with their signs made consistent
+        let (n, r, queries) = twist_residues(&curve, &twist, 1 << 11, oracle);
+        assert_eq!(r, BigUint::from(11u32 * 107 * 197 * 1621));
+        // one for each factor, and one to line up the sign of each nonzero residue
+        // but the first, which the others are lined up against
+        let nonzero = [11u32, 107, 197, 1621]
+            .iter()
+            .filter(|f| !(&bob_secret % **f).is_zero())
+            .count();
+        assert_eq!(queries, 4 + nonzero.saturating_sub(1));
+
+        let residue = &bob_secret % &r;
+        assert!(n == residue || n == (&r - &residue) % &r);
+    }
+
+    // a secret below 2^48 leaves the kangaroo about 2^20 to cover past the twist
+    #[test]
+    fn test_challenge_4c() {
+        let params = EcParams::cryptopals();
+        let curve = MontgomeryCurve::cryptopals();
+        let twist = twist_order(&curve.p, &(&params.q * 8u32));
+
+        let most = BigUint::from(1u64 << 48);
+        let bob_secret = rand::thread_rng().gen_biguint_below(&most);
+        let bob_public = curve.ladder(&BigUint::from(4u32), &bob_secret);
+        let oracle = get_ladder_mac_oracle(
+            curve.clone(),
+            bob_secret.clone(),
+            b"crazy flamboyant for the rap enjoyment",
+        );
+
+        let result =
+            twist_attack_in(&params, &curve, &twist, &bob_public, &most, 1 << 11, oracle).unwrap();
+
+        // q - x is far above the bound, so only x itself is in the interval
+        assert_eq!(result.secret, bob_secret);
+    }
+
+    // the kangaroo over 2^40 on the curve takes minutes, so this only runs with
+    // `--ignored`
+    #[test]
+    #[ignore]
+    fn test_challenge_4d() {
+        let params = EcParams::cryptopals();
+        let curve = MontgomeryCurve::cryptopals();
+        let twist = twist_order(&curve.p, &(&params.q * 8u32));
+
+        let (bob_secret, _) = ec::generate_keypair(&params);
+        let bob_public = curve.ladder(&BigUint::from(4u32), &bob_secret);
+        let oracle = get_ladder_mac_oracle(
+            curve.clone(),
+            bob_secret.clone(),
+            b"crazy flamboyant for the rap enjoyment",
+        );
+
+        let result = twist_attack(&params, &curve, &twist, &bob_public, 1 << 24, oracle).unwrap();
+        println!("recovered in {} queries", result.queries);
+
+        // x and q - x give the same u, so Bob's secret is only known up to sign
+        assert!(result.secret == bob_secret || result.secret == &params.q - &bob_secret);
+        assert_eq!(
+            curve.ladder(&BigUint::from(4u32), &result.secret),
+            bob_public
+        );
+    }
+
+    #[test]
+    fn test_challenge_5a() {
+        let params = EcParams::cryptopals();
+        let (secret, public) = ec::generate_keypair(&params);
+        let message = b"I'm the one who signed this";
+
+        let signature = ecdsa::sign(&params, &secret, message);
+        assert!(ecdsa::verify(&params, &public, message, &signature));
+        assert!(!ecdsa::verify(&params, &public, b"I'm not", &signature));
+
+        // Eve's key verifies the same signature, with a generator of her choosing
+        let (eve_params, _, eve_public) = ecdsa_dsks(&params, &public, message, &signature);
+        assert_ne!(eve_params.g, params.g);
+        assert_ne!(eve_public, public);
+        assert!(ecdsa::verify(&eve_params, &eve_public, message, &signature));
+    }
+
+    #[test]
+    fn test_challenge_5b() {
+        let (public, private) = rsa::generate_keypair(512, 65537);
+        let message = b"I'm the one who signed this";
+
+        let signature = rsa::sign(&private, message);
+        assert!(rsa::verify(&public, message, &signature));
+        assert!(!rsa::verify(&public, b"I'm not", &signature));
+
+        // the same signature, now over a different message under Eve's key
+        let forged = b"I owe Eve a million dollars";
+        let (eve_public, eve_private) = rsa_dsks(&public, forged, &signature);
+        assert!(rsa::verify(&eve_public, forged, &signature));
+        assert_eq!(rsa::sign(&eve_private, forged), signature);
+    }
+
+    // rows of (numerator, denominator) pairs
+    fn rationals(rows: &[Vec<(i64, i64)>]) -> Vec<Vector> {
+        rows.iter()
+            .map(|row| {
+                row.iter()
+                    .map(|(n, d)| BigRational::new(BigInt::from(*n), BigInt::from(*d)))
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_challenge_6a() {
+        let basis = from_integers(&[vec![1, 1, 1], vec![-1, 0, 2], vec![3, 5, 6]]);
+        let orthogonal = gram_schmidt(&basis);
+        assert_eq!(orthogonal[0], basis[0]);
+        for i in 0..3 {
+            for j in 0..i {
+                assert!(dot(&orthogonal[i], &orthogonal[j]).is_zero());
+            }
+        }
+
+        let delta = BigRational::new(BigInt::from(3), BigInt::from(4));
+        assert_eq!(
+            lll(&basis, &delta),
+            from_integers(&[vec![0, 1, 0], vec![1, 0, 1], vec![-1, 0, 2]])
+        );
+
+        // the example from the challenge, with delta = 0.99
+        let basis = rationals(&[
+            vec![(-2, 1), (0, 1), (2, 1), (0, 1)],
+            vec![(1, 2), (-1, 1), (0, 1), (0, 1)],
+            vec![(-1, 1), (0, 1), (-2, 1), (1, 2)],
+            vec![(-1, 1), (1, 1), (1, 1), (2, 1)],
+        ]);
+        let delta = BigRational::new(BigInt::from(99), BigInt::from(100));
+        assert_eq!(
+            lll(&basis, &delta),
+            rationals(&[
+                vec![(1, 2), (-1, 1), (0, 1), (0, 1)],
+                vec![(-1, 1), (0, 1), (-2, 1), (1, 2)],
+                vec![(-1, 2), (0, 1), (1, 1), (2, 1)],
+                vec![(-3, 2), (-1, 1), (2, 1), (0, 1)],
+            ])
+        );
+    }
+
+    #[test]
+    fn test_challenge_6b() {
+        let params = EcParams::cryptopals();
+        let (secret, public) = ec::generate_keypair(&params);
+        let oracle = get_biased_signing_oracle(params.clone(), secret.clone(), 8);
+
+        let signatures: Vec<(Vec<u8>, ecdsa::Signature)> = (0..22u32)
+            .map(|i| {
+                let message = format!("message number {i}").into_bytes();
+                let signature = oracle(&message);
+                (message, signature)
+            })
+            .collect();
+        assert!(signatures
+            .iter()
+            .all(|(message, signature)| ecdsa::verify(&params, &public, message, signature)));
+
+        assert_eq!(hnp_attack(&params, &public, &signatures, 8), Some(secret));
+    }
+
+    #[test]
+    fn test_challenge_7a() {
+        // x * x^127 wraps around to x^7 + x^2 + x + 1
+        let x = Gf128(1 << 126);
+        assert_eq!(x * x.pow(127), Gf128(0xe1 << 120));
+        let a = Gf128::random();
+        assert_eq!(a * a.inverse().unwrap(), Gf128::one());
+        assert_eq!(Gf128::zero().inverse(), None);
+
+        let hex = |s: &str| hex_to_bytes(s).unwrap();
+        let key = hex("00000000000000000000000000000000");
+        let iv = hex("000000000000000000000000");
+        let h = auth_key(&key).unwrap();
+        assert_eq!(
+            bytes_to_hex(h.to_bytes().to_vec()),
+            "66e94bd4ef8a2c3b884cfa59ca342b2e"
+        );
+
+        // NIST test cases 1 to 4
+        let (ciphertext, tag) = encrypt_gcm(&key, &iv, &[], &[]).unwrap();
+        assert!(ciphertext.is_empty());
+        assert_eq!(bytes_to_hex(tag), "58e2fccefa7e3061367f1d57a4e7455a");
+
+        let (ciphertext, tag) = encrypt_gcm(&key, &iv, &[], &[0; 16]).unwrap();
+        assert_eq!(
+            bytes_to_hex(ciphertext.clone()),
+            "0388dace60b6a392f328c2b971b2fe78"
+        );
+        assert_eq!(bytes_to_hex(tag), "ab6e47d42cec13bdf53a67b21257bddf");
+        assert_eq!(
+            bytes_to_hex(ghash(h, &[], &ciphertext).to_bytes().to_vec()),
+            "f38cbb1ad69223dcc3457ae5b6b0f885"
+        );
+
+        let key = hex("feffe9928665731c6d6a8f9467308308");
+        let iv = hex("cafebabefacedbaddecaf888");
+        let plaintext = hex(concat!(
+            "d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a72",
+            "1c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b391aafd255"
+        ));
+        let expected = hex(concat!(
+            "42831ec2217774244b7221b784d0d49ce3aa212f2c02a4e035c17e2329aca12e",
+            "21d514b25466931c7d8f6a5aac84aa051ba30b396a0aac973d58e091473f5985"
+        ));
+        let (ciphertext, tag) = encrypt_gcm(&key, &iv, &[], &plaintext).unwrap();
+        assert_eq!(ciphertext, expected);
+        assert_eq!(bytes_to_hex(tag), "4d5c2af327cd64a62cf35abd2ba6fab4");
+
+        let aad = hex("feedfacedeadbeeffeedfacedeadbeefabaddad2");
+        let (ciphertext, tag) = encrypt_gcm(&key, &iv, &aad, &plaintext[..60]).unwrap();
+        assert_eq!(ciphertext, expected[..60]);
+        assert_eq!(
+            bytes_to_hex(tag.clone()),
+            "5bc94fbc3221a5db94fae95ae7121a47"
+        );
+
+        assert_eq!(
+            decrypt_gcm(&key, &iv, &aad, &ciphertext, &tag),
+            Ok(plaintext[..60].to_vec())
+        );
+        let mut forged = ciphertext.clone();
+        forged[0] ^= 1;
+        assert_eq!(
+            decrypt_gcm(&key, &iv, &aad, &forged, &tag),
+            Err(CryptoError::OracleRejection)
+        );
+        assert_eq!(
+            decrypt_gcm(&key, &iv, &aad[1..], &ciphertext, &tag),
+            Err(CryptoError::OracleRejection)
+        );
+
+        assert_eq!(
+            decrypt_gcm(&key[..8], &iv, &aad, &ciphertext, &tag),
+            Err(CryptoError::InvalidKeyLength(8))
+        );
+        assert_eq!(
+            encrypt_gcm(&key, &iv[..8], &aad, &plaintext),
+            Err(CryptoError::InvalidIvLength(8))
+        );
+        assert_eq!(auth_key(&[]), Err(CryptoError::InvalidKeyLength(0)));
+        assert!(get_truncated_gcm_oracle(key[..8].to_vec(), iv, 2).is_err());
+    }
+
+    #[test]
+    fn test_challenge_7b() {
+        let linear = |r: Gf128| Polynomial::new(vec![r, Gf128::one()]);
+        let (a, b, c) = (Gf128::random(), Gf128::random(), Gf128::random());
+
+        let f = Polynomial::random(6);
+        let g = Polynomial::random(3);
+        let (quotient, remainder) = f.divmod(&g);
+        assert!(remainder.degree().unwrap_or(0) < 3);
+        assert_eq!(&(&quotient * &g) + &remainder, f);
+        assert_eq!((&f * &g).gcd(&(&g * &linear(a))), g.monic());
+        assert_eq!(f.evaluate(a), f.rem(&linear(a)).evaluate(Gf128::zero()));
+        assert_eq!(
+            Polynomial::x().modpow(&(BigUint::from(1u32) << 128), &linear(a)),
+            Polynomial::constant(a)
+        );
+
+        // (x + a)^2 (x + b)^3 (x + c)
+        let squared = &linear(a) * &linear(a);
+        let cubed = &(&linear(b) * &linear(b)) * &linear(b);
+        let f = &(&squared * &cubed) * &linear(c);
+        let mut factors = square_free_factorization(&f);
+        factors.sort_by_key(|(_, m)| *m);
+        assert_eq!(
+            factors,
+            vec![(linear(c), 1), (linear(a), 2), (linear(b), 3)]
+        );
+
+        // a random quartic is most likely not all linear factors
+        let g = Polynomial::random(4).monic();
+        let f = &(&linear(a) * &linear(b)) * &g;
+        let split = distinct_degree_factorization(&f);
+        assert_eq!(
+            split
+                .iter()
+                .fold(Polynomial::one(), |acc, (factor, _)| &acc * factor),
+            f
+        );
+        let (linears, _) = split.iter().find(|(_, d)| *d == 1).unwrap();
+        let mut found = equal_degree_factorization(linears, 1);
+        found.retain(|factor| *factor == linear(a) || *factor == linear(b));
+        assert_eq!(found.len(), 2);
+
+        let mut expected = vec![a, b, c];
+        expected.sort_by_key(|r| r.0);
+        let f = &(&(&linear(a) * &linear(b)) * &linear(c)) * &linear(c);
+        let mut found = roots(&f.scale(b));
+        found.sort_by_key(|r| r.0);
+        assert_eq!(found, expected);
+    }
+
+    #[test]
+    fn test_challenge_7c() {
+        let key: [u8; 16] = rand::random();
+        let iv: [u8; 12] = rand::random();
+        let h = auth_key(&key).unwrap();
+
+        // three messages under the same nonce
+        let messages: Vec<GcmMessage> = [
+            (
+                &b"alice"[..],
+                &b"Transfer $100 from Alice to Bob, for lunch"[..],
+            ),
+            (b"bob", b"Transfer $25 from Bob to Carol"),
+            (
+                b"carol",
+                b"Transfer $3000 from Carol to Dave, before Friday at noon",
+            ),
+        ]
+        .iter()
+        .map(|(aad, plaintext)| {
+            let (ciphertext, tag) = encrypt_gcm(&key, &iv, aad, plaintext).unwrap();
+            GcmMessage {
+                aad: aad.to_vec(),
+                ciphertext,
+                tag,
+            }
+        })
+        .collect();
+        assert_eq!(auth_key_candidates(&messages), vec![h]);
+        assert!(auth_key_candidates(&messages[..1]).is_empty());
+        assert!(auth_key_candidates(&[]).is_empty());
+
+        // flip the known plaintext of the first message under the keystream
+        let original = b"Transfer $100 from Alice to Bob, for lunch";
+        let wanted = b"Transfer $900 from Alice to Eve, for lunch";
+        let ciphertext: Vec<u8> = messages[0]
+            .ciphertext
+            .iter()
+            .zip(original.iter().zip(wanted))
+            .map(|(c, (p, w))| c ^ p ^ w)
+            .collect();
+        let tag = forge_tag(h, &messages[1], b"eve", &ciphertext);
+        assert_eq!(
+            decrypt_gcm(&key, &iv, b"eve", &ciphertext, &tag),
+            Ok(wanted.to_vec())
+        );
+    }
+
+    #[test]
+    fn test_challenge_8a() {
+        let (a, b) = (Gf128::random(), Gf128::random());
+        let v = Gf2Vector::from_gf128(b);
+        assert_eq!(v.to_gf128(), b);
+        assert_eq!(multiplication_matrix(a).mul_vector(&v).to_gf128(), a * b);
+        assert_eq!(squaring_matrix().mul_vector(&v).to_gf128(), b * b);
+        assert_eq!(
+            &multiplication_matrix(a) * &multiplication_matrix(b),
+            multiplication_matrix(a * b)
+        );
+
+        let m = &Gf2Matrix::identity(128) * &squaring_matrix();
+        assert_eq!(m, squaring_matrix());
+        assert_eq!(m.rank(), 128);
+        assert!(m.kernel().is_empty());
+
+        // x + y, y + z and x + z add up to zero, which leaves one dimension
+        let rows: Vec<Gf2Vector> = [[1, 1, 0, 0], [0, 1, 1, 0], [1, 0, 1, 0]]
+            .iter()
+            .map(|row| {
+                let mut v = Gf2Vector::zeros(4);
+                row.iter().enumerate().for_each(|(i, b)| v.set(i, *b == 1));
+                v
+            })
+            .collect();
+        let m = Gf2Matrix::from_rows(4, rows);
+        assert_eq!(m.rank(), 2);
+        let kernel = m.kernel();
+        assert_eq!(kernel.len(), 2);
+        assert!(kernel.iter().all(|v| m.mul_vector(v).is_zero()));
+        assert_eq!(m.transpose().transpose(), m);
+
+        let m = Gf2Matrix::from_columns(
+            40,
+            &(0..60).map(|_| Gf2Vector::random(40)).collect::<Vec<_>>(),
+        );
+        let kernel = m.kernel();
+        assert_eq!(kernel.len(), 60 - m.rank());
+        assert!(kernel.iter().all(|v| m.mul_vector(v).is_zero()));
+    }
+
+    #[test]
+    fn test_challenge_8b() {
+        let key: [u8; 16] = rand::random();
+        let iv: [u8; 12] = rand::random();
+
+        // 16 bit tags and 2^9 - 1 blocks, which is a quick version of the real thing
+        let plaintext = vec![b'A'; 16 * 511];
+        let (ciphertext, tag) = encrypt_gcm(&key, &iv, &[], &plaintext).unwrap();
+        let oracle = get_truncated_gcm_oracle(key.to_vec(), iv.to_vec(), 2).unwrap();
+
+        let result = ferguson_attack(&ciphertext, &tag[..2], oracle).unwrap();
+        assert_eq!(result.h, auth_key(&key).unwrap());
+
+        // lengths the attack or Bob can't work with
+        let never = |_: &[u8], _: &[u8]| false;
+        assert_eq!(
+            ferguson_attack(&ciphertext[..20], &tag[..2], never).unwrap_err(),
+            CryptoError::BadLength(20)
+        );
+        assert_eq!(
+            ferguson_attack(&[], &tag[..2], never).unwrap_err(),
+            CryptoError::BadLength(0)
+        );
+        assert_eq!(
+            ferguson_attack(&ciphertext, &[], never).unwrap_err(),
+            CryptoError::BadLength(0)
+        );
+        assert!(get_truncated_gcm_oracle(key.to_vec(), iv.to_vec(), 17).is_err());
+        assert!(get_truncated_gcm_oracle(key.to_vec(), iv.to_vec(), 0).is_err());
+
+        // 8 rows zeroed to begin with, so 1 in 2^8 forgeries got through, and never
+        // fewer than 1 in 2 at the end
+        let first = &result.iterations[0];
+        assert_eq!((first.known_bits, first.zeroed_rows), (0, 8));
+        assert_eq!(first.expected_success_rate(), 1.0 / 256.0);
+        assert!(result.iterations.iter().all(|i| i.zeroed_rows <= 15));
+        assert!(result
+            .iterations
+            .windows(2)
+            .all(|w| w[0].known_bits <= w[1].known_bits));
+    }
+
+    // 2^16 forgeries of 2^17 blocks each just to get going, so this only runs with
+    // --ignored
+    #[test]
+    #[ignore]
+    fn test_challenge_8c() {
+        let key: [u8; 16] = rand::random();
+        let iv: [u8; 12] = rand::random();
+
+        let plaintext = vec![b'A'; 16 * ((1 << 17) - 1)];
+        let (ciphertext, tag) = encrypt_gcm(&key, &iv, &[], &plaintext).unwrap();
+        let oracle = get_truncated_gcm_oracle(key.to_vec(), iv.to_vec(), 4).unwrap();
+
+        let result = ferguson_attack(&ciphertext, &tag[..4], oracle).unwrap();
+        assert_eq!(result.h, auth_key(&key).unwrap());
+        assert_eq!(result.iterations[0].zeroed_rows, 16);
+        for iteration in &result.iterations {
+            println!(
+                "{:>3} bits known, {:>2} rows zeroed: {} queries, expected {:.0}",
+                iteration.known_bits,
+                iteration.zeroed_rows,
+                iteration.queries,
+                1.0 / iteration.expected_success_rate()
+            );
+        }
+    }
+
+    #[test]
+    fn test_challenge_10a() {
+        let p = (BigUint::from(1u32) << 61u32) - 1u32;
+        let group = ModP(&p);
+        let fault = FaultModel { bits: 10 };
+        let g = BigUint::from(37u32);
+        let secret = rand::thread_rng()
+            .gen_biguint_range(&BigUint::from(1u32), &(BigUint::from(1u32) << 40u32));
+        let public = g.modpow(&secret, &p);
+
+        // the fault really does leave Bob with the wrong answer
+        let x = loop {
+            let x = rand::thread_rng().gen_biguint_range(&BigUint::from(2u32), &p);
+            if fault.occurs(&group, &x, &x) {
+                break x;
+            }
+        };
+        assert_ne!(fault.op(&group, &x, &x), group.op(&x, &x));
+
+        let oracle = get_handshake_oracle(ModP(&p), fault, secret.clone());
+        let random = || rand::thread_rng().gen_biguint_range(&BigUint::from(2u32), &p);
+        let is_secret = |d: &BigUint| g.modpow(d, &p) == public;
+        let result = fault_attack(&group, fault, 61, 5, random, oracle, is_secret).unwrap();
+        assert_eq!(result.secret, secret);
+        assert!(result.queries >= secret.bits() as usize - 1);
+    }
+
+    #[test]
+    fn test_challenge_10b() {
+        // a 20 bit secret, as every guess needs Bob's scalar multiplication run
+        // through about 2^8 times
+        let params = EcParams::cryptopals();
+        let fault = FaultModel { bits: 8 };
+        let secret =
+            rand::thread_rng().gen_biguint_range(&BigUint::from(1u32), &BigUint::from(1u32 << 20));
+        let public = params.curve.scalar_mul(&params.g, &secret);
+
+        let oracle = get_handshake_oracle(params.curve.clone(), fault, secret.clone());
+        let random = || params.curve.random_point();
+        let is_secret = |d: &BigUint| params.curve.scalar_mul(&params.g, d) == public;
+        let result = fault_attack(&params.curve, fault, 128, 6, random, oracle, is_secret).unwrap();
+        assert_eq!(result.secret, secret);
+    }
+}
@@ -4,13 +4,24 @@ use aes::{
     cipher::{generic_array::GenericArray, BlockDecrypt, BlockEncrypt, KeyInit},
     Aes128,
 };
-use base64::{engine::general_purpose, Engine};
 use rand::{distributions::Standard, Rng};
 
-use super::xor::xor;
+use super::{conversion::base64_to_bytes, error::CryptoError, xor::xor};
 
-pub fn decrypt_ecb(key: &[u8], bytes: Vec<u8>) -> Vec<u8> {
-    let cipher = Aes128::new(key.into());
+pub(crate) fn cipher(key: &[u8]) -> Result<Aes128, CryptoError> {
+    Aes128::new_from_slice(key).map_err(|_| CryptoError::InvalidKeyLength(key.len()))
+}
+
+fn check_blocks(bytes: &[u8]) -> Result<(), CryptoError> {
+    if !bytes.len().is_multiple_of(16) {
+        return Err(CryptoError::BadLength(bytes.len()));
+    }
+    Ok(())
+}
+
+pub fn decrypt_ecb(key: &[u8], bytes: Vec<u8>) -> Result<Vec<u8>, CryptoError> {
+    let cipher = cipher(key)?;
+    check_blocks(&bytes)?;
 
     let full = bytes
         .as_slice()
@@ -21,11 +32,12 @@ pub fn decrypt_ecb(key: &[u8], bytes: Vec<u8>) -> Vec<u8> {
             block
         })
         .collect::<Vec<u8>>();
-    full
+    Ok(full)
 }
 
-pub fn encrypt_ecb(key: &[u8], bytes: Vec<u8>) -> Vec<u8> {
-    let cipher = Aes128::new(key.into());
+pub fn encrypt_ecb(key: &[u8], bytes: Vec<u8>) -> Result<Vec<u8>, CryptoError> {
+    let cipher = cipher(key)?;
+    check_blocks(&bytes)?;
 
     let full = bytes
         .as_slice()
@@ -36,7 +48,7 @@ pub fn encrypt_ecb(key: &[u8], bytes: Vec<u8>) -> Vec<u8> {
             block
         })
         .collect::<Vec<u8>>();
-    full
+    Ok(full)
 }
 
 pub fn pkcs7_padding(bytes: &[u8], block_size: usize) -> Vec<u8> {
@@ -47,8 +59,24 @@ pub fn pkcs7_padding(bytes: &[u8], block_size: usize) -> Vec<u8> {
     v
 }
 
-pub fn encrypt_cbc(key: &[u8], bytes: Vec<u8>, iv: &[u8]) -> Vec<u8> {
-    let cipher = Aes128::new(key.into());
+// Strips PKCS#7 padding, which has to be there and be consistent
+pub fn pkcs7_unpad(bytes: &[u8], block_size: usize) -> Result<Vec<u8>, CryptoError> {
+    if bytes.is_empty() || !bytes.len().is_multiple_of(block_size) {
+        return Err(CryptoError::BadLength(bytes.len()));
+    }
+    let n = bytes[bytes.len() - 1] as usize;
+    if n == 0 || n > block_size || bytes[bytes.len() - n..].iter().any(|b| *b as usize != n) {
+        return Err(CryptoError::InvalidPadding);
+    }
+    Ok(bytes[..bytes.len() - n].to_vec())
+}
+
+pub fn encrypt_cbc(key: &[u8], bytes: Vec<u8>, iv: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    let cipher = cipher(key)?;
+    check_blocks(&bytes)?;
+    if iv.len() != 16 {
+        return Err(CryptoError::InvalidIvLength(iv.len()));
+    }
 
     let full = bytes
         .as_slice()
@@ -71,11 +99,15 @@ pub fn encrypt_cbc(key: &[u8], bytes: Vec<u8>, iv: &[u8]) -> Vec<u8> {
             (cipher_text, new_encrypted_bytes)
         });
 
-    return full.1;
+    Ok(full.1)
 }
 
-pub fn decrypt_cbc(key: &[u8], bytes: Vec<u8>, iv: &[u8]) -> Vec<u8> {
-    let cipher = Aes128::new(key.into());
+pub fn decrypt_cbc(key: &[u8], bytes: Vec<u8>, iv: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    let cipher = cipher(key)?;
+    check_blocks(&bytes)?;
+    if iv.len() != 16 {
+        return Err(CryptoError::InvalidIvLength(iv.len()));
+    }
 
     let full = bytes
         .as_slice()
@@ -95,7 +127,29 @@ pub fn decrypt_cbc(key: &[u8], bytes: Vec<u8>, iv: &[u8]) -> Vec<u8> {
             (chunk.to_vec(), new_decrypted_bytes)
         });
 
-    return full.1;
+    Ok(full.1)
+}
+
+pub fn encrypt_ctr(key: &[u8], bytes: Vec<u8>, nonce: u64) -> Result<Vec<u8>, CryptoError> {
+    let cipher = cipher(key)?;
+
+    Ok(bytes
+        .as_slice()
+        .chunks(16)
+        .enumerate()
+        .flat_map(|(counter, chunk)| {
+            // 64 bit little endian nonce, then 64 bit little endian block counter
+            let counter_block = [nonce.to_le_bytes(), (counter as u64).to_le_bytes()].concat();
+            let mut keystream = *GenericArray::from_slice(&counter_block);
+            cipher.encrypt_block(&mut keystream);
+
+            xor(chunk, &keystream[..chunk.len()])
+        })
+        .collect())
+}
+
+pub fn decrypt_ctr(key: &[u8], bytes: Vec<u8>, nonce: u64) -> Result<Vec<u8>, CryptoError> {
+    encrypt_ctr(key, bytes, nonce)
 }
 
 pub fn detect_ebc(input: &[u8]) -> bool {
@@ -129,11 +183,11 @@ pub fn get_encryption_oracle() -> impl Fn(&[u8]) -> (bool, Vec<u8>) {
 
         if cbc_mode {
             let iv: [u8; 16] = rng.gen();
-            let encrypted = encrypt_cbc(&key, plain_text, &iv);
+            let encrypted = encrypt_cbc(&key, plain_text, &iv).unwrap();
 
             return (false, encrypted);
         } else {
-            let encrypted = encrypt_ecb(&key, plain_text);
+            let encrypted = encrypt_ecb(&key, plain_text).unwrap();
             return (true, encrypted);
         }
     };
@@ -143,9 +197,7 @@ pub fn get_encryption_oracle() -> impl Fn(&[u8]) -> (bool, Vec<u8>) {
 
 pub fn get_encryption_oracle_with_suffix() -> impl Fn(&[u8]) -> Vec<u8> {
     let mut rng = rand::thread_rng();
-    let suffix = general_purpose::STANDARD
-    .decode("Um9sbGluJyBpbiBteSA1LjAKV2l0aCBteSByYWctdG9wIGRvd24gc28gbXkgaGFpciBjYW4gYmxvdwpUaGUgZ2lybGllcyBvbiBzdGFuZGJ5IHdhdmluZyBqdXN0IHRvIHNheSBoaQpEaWQgeW91IHN0b3A/IE5vLCBJIGp1c3QgZHJvdmUgYnkK")
-    .unwrap();
+    let suffix = base64_to_bytes("Um9sbGluJyBpbiBteSA1LjAKV2l0aCBteSByYWctdG9wIGRvd24gc28gbXkgaGFpciBjYW4gYmxvdwpUaGUgZ2lybGllcyBvbiBzdGFuZGJ5IHdhdmluZyBqdXN0IHRvIHNheSBoaQpEaWQgeW91IHN0b3A/IE5vLCBJIGp1c3QgZHJvdmUgYnkK").unwrap();
 
     // do all the random things
     let key: [u8; 16] = rng.gen();
@@ -154,7 +206,7 @@ pub fn get_encryption_oracle_with_suffix() -> impl Fn(&[u8]) -> Vec<u8> {
         let mut plain_text = [input.to_vec(), suffix.to_vec()].concat();
         plain_text = pkcs7_padding(&plain_text, 16);
 
-        let encrypted = encrypt_ecb(&key, plain_text);
+        let encrypted = encrypt_ecb(&key, plain_text).unwrap();
         return encrypted;
     };
 
@@ -1,7 +1,11 @@
 use phf::phf_map;
 use std::{collections::HashMap, str::from_utf8};
 
-use super::{conversion::hex_to_bytes, xor::xor_with_key};
+use super::{
+    conversion::{hex_to_bytes, transpose},
+    hamming::hamming_distance_bytes,
+    xor::{xor_with_key, xor_with_repeating_key},
+};
 
 static ASCII_FREQ: phf::Map<u8, f64> = phf_map! {
     32u8 => 0.167564443682168,
@@ -135,3 +139,79 @@ pub fn most_likely_encoded(strs: Vec<&str>) -> (f64, u8, String) {
         .min_by(|a: &(f64, u8, String), b| a.0.partial_cmp(&b.0).unwrap())
         .unwrap()
 }
+
+// The key sizes to try, and how many of the most likely of them to solve
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeySizeRange {
+    pub min: usize,
+    pub max: usize,
+    pub top: usize,
+}
+
+impl Default for KeySizeRange {
+    fn default() -> Self {
+        KeySizeRange {
+            min: 2,
+            max: 40,
+            top: 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Candidate {
+    pub key: Vec<u8>,
+    pub plaintext: Vec<u8>,
+    // of the whole plaintext, lower is more like English
+    pub score: f64,
+}
+
+// The Hamming distance between every pair of key size blocks, per bit of key,
+// averaged. Under the right key size each pair is two plaintexts xored together,
+// which differ in fewer bits than random bytes do.
+pub fn key_size_distance(ct: &[u8], key_size: usize) -> Option<f64> {
+    let blocks = ct.chunks_exact(key_size).collect::<Vec<_>>();
+    if blocks.len() < 2 {
+        return None;
+    }
+    let mut total = 0;
+    for (i, a) in blocks.iter().enumerate() {
+        for b in &blocks[i + 1..] {
+            total += hamming_distance_bytes(a, b) as u64;
+        }
+    }
+    let pairs = blocks.len() * (blocks.len() - 1) / 2;
+    Some(total as f64 / (pairs * key_size) as f64)
+}
+
+// Repeating key xor: each of the likeliest key sizes is solved a column at a
+// time as single byte xor, and the full decryptions ranked by score, best first
+pub fn break_repeating_xor(ct: &[u8], key_sizes: KeySizeRange) -> Vec<Candidate> {
+    let mut distances = (key_sizes.min.max(1)..=key_sizes.max)
+        .filter_map(|key_size| key_size_distance(ct, key_size).map(|d| (key_size, d)))
+        .collect::<Vec<_>>();
+    distances.sort_by(|a, b| a.1.partial_cmp(&b.1).unwrap());
+
+    let mut candidates = distances
+        .into_iter()
+        .take(key_sizes.top)
+        .filter_map(|(key_size, _)| {
+            let key = (0..key_size)
+                .map(|offset| {
+                    freq_analysis_iter(&transpose(ct, key_size, offset))
+                        .min_by(|a, b| a.0.partial_cmp(&b.0).unwrap())
+                        .map(|(_, k, _)| k)
+                })
+                .collect::<Option<Vec<u8>>>()?;
+            let plaintext = xor_with_repeating_key(ct, &key).ok()?;
+            let score = character_freq_score(&plaintext)?;
+            Some(Candidate {
+                key,
+                plaintext,
+                score,
+            })
+        })
+        .collect::<Vec<_>>();
+    candidates.sort_by(|a, b| a.score.partial_cmp(&b.score).unwrap());
+    candidates
+}
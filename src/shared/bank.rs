@@ -0,0 +1,184 @@
+use std::{collections::HashMap, str::FromStr};
+
+use rand::Rng;
+
+use super::{aes::pkcs7_padding, error::CryptoError, kv::parse_string, mac::cbc_mac, xor::xor};
+
+#[derive(Debug, PartialEq)]
+pub struct Transfer {
+    pub from: u32,
+    pub to: u32,
+    pub amount: u64,
+}
+
+pub struct Server {
+    key: [u8; 16],
+}
+
+// a client is logged in to a single account, and will only sign transfers from it
+pub struct Client {
+    key: [u8; 16],
+    pub account: u32,
+}
+
+impl Server {
+    pub fn new() -> Server {
+        Server {
+            key: rand::thread_rng().gen(),
+        }
+    }
+
+    pub fn client(&self, account: u32) -> Client {
+        Client {
+            key: self.key,
+            account,
+        }
+    }
+
+    // message || IV || MAC, with `from=#{from}&to=#{to}&amount=#{amount}`
+    pub fn process(&self, request: &[u8]) -> Result<Transfer, CryptoError> {
+        if request.len() < 32 {
+            return Err(CryptoError::BadLength(request.len()));
+        }
+        let (message, iv_mac) = request.split_at(request.len() - 32);
+        let (iv, mac) = iv_mac.split_at(16);
+        if cbc_mac(&self.key, message, iv).as_deref() != Ok(mac) {
+            return Err(CryptoError::OracleRejection);
+        }
+
+        let message = String::from_utf8_lossy(message);
+        let params = parse_string(&message);
+        Ok(Transfer {
+            from: field(&params, "from")?,
+            to: field(&params, "to")?,
+            amount: field(&params, "amount")?,
+        })
+    }
+
+    // message || MAC under a zero IV, with `from=#{from}&tx_list=#{to:amount(;to:amount)*}`.
+    // entries of the list that don't parse are skipped
+    pub fn process_many(&self, request: &[u8]) -> Result<Vec<Transfer>, CryptoError> {
+        if request.len() < 16 {
+            return Err(CryptoError::BadLength(request.len()));
+        }
+        let (message, mac) = request.split_at(request.len() - 16);
+        if cbc_mac(&self.key, message, &[0; 16]).as_deref() != Ok(mac) {
+            return Err(CryptoError::OracleRejection);
+        }
+
+        let message = String::from_utf8_lossy(message);
+        let params = parse_string(&message);
+        let from = field(&params, "from")?;
+        let tx_list = params
+            .get("tx_list")
+            .ok_or(CryptoError::MissingField("tx_list"))?;
+
+        Ok(tx_list
+            .split(';')
+            .filter_map(|tx| {
+                let (to, amount) = tx.split_once(':')?;
+                Some(Transfer {
+                    from,
+                    to: to.parse().ok()?,
+                    amount: amount.parse().ok()?,
+                })
+            })
+            .collect())
+    }
+}
+
+fn field<T: FromStr>(params: &HashMap<&str, &str>, name: &'static str) -> Result<T, CryptoError> {
+    params
+        .get(name)
+        .and_then(|v| v.parse().ok())
+        .ok_or(CryptoError::MissingField(name))
+}
+
+impl Default for Server {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Client {
+    pub fn transfer(&self, to: u32, amount: u64) -> Vec<u8> {
+        let message = format!("from={}&to={}&amount={}", self.account, to, amount);
+        let iv: [u8; 16] = rand::thread_rng().gen();
+        let mac = cbc_mac(&self.key, message.as_bytes(), &iv).expect("a 16 byte key and IV");
+
+        [message.as_bytes(), &iv, &mac].concat()
+    }
+
+    pub fn transfer_many(&self, transfers: &[(u32, u64)]) -> Vec<u8> {
+        let tx_list = transfers
+            .iter()
+            .map(|(to, amount)| format!("{}:{}", to, amount))
+            .collect::<Vec<String>>()
+            .join(";");
+        let message = format!("from={}&tx_list={}", self.account, tx_list);
+        let mac = cbc_mac(&self.key, message.as_bytes(), &[0; 16]).expect("a 16 byte key and IV");
+
+        [message.as_bytes(), &mac].concat()
+    }
+}
+
+// Rewrites the sender of a captured `process` request to `victim` by flipping bits
+// of the IV, which only affects the first block. The ids must be the same length.
+pub fn forge_sender(request: &[u8], victim: u32) -> Vec<u8> {
+    let (message, iv_mac) = request.split_at(request.len() - 32);
+    let (iv, mac) = iv_mac.split_at(16);
+
+    let forged = String::from_utf8_lossy(message)
+        .split_once('&')
+        .map(|(_, rest)| format!("from={}&{}", victim, rest))
+        .unwrap();
+    assert_eq!(
+        forged.len(),
+        message.len(),
+        "account ids must be the same length"
+    );
+
+    let first_block = &message[..16];
+    let forged_block = &forged.as_bytes()[..16];
+    let forged_iv = xor(&xor(iv, first_block), forged_block);
+
+    [forged.as_bytes(), &forged_iv, mac].concat()
+}
+
+// Appends a transfer to `attacker` onto a captured `process_many` request. The MAC
+// of the captured message is the CBC state after it, so gluing on the attacker's
+// own signed message with its first block XORed by that MAC leaves the attacker's
+// MAC valid for the whole thing.
+//
+// Only that first block turns to garbage, inside the victim's tx_list, and the rest
+// of the attacker's message is read as it stands. So the attacker's own
+// `from=..&tx_list=` has to end within the block, or its `=` would spoil the
+// victim's tx_list; that leaves out ids of more than two digits. The garbage fails
+// the same way when it happens to contain a delimiter; sniff another request and
+// try again.
+pub fn forge_extension(
+    victim_request: &[u8],
+    attacker: &Client,
+    amount: u64,
+) -> Result<Vec<u8>, CryptoError> {
+    let header = format!("from={}&tx_list=", attacker.account).len();
+    if header > 16 {
+        return Err(CryptoError::BadLength(header));
+    }
+    let (victim_message, victim_mac) = victim_request.split_at(victim_request.len() - 16);
+    let victim_message = pkcs7_padding(victim_message, 16);
+
+    // the first transfer runs into the garbage, so it is sacrificed
+    let own = attacker.transfer_many(&[(attacker.account, 1), (attacker.account, amount)]);
+    let (own_message, own_mac) = own.split_at(own.len() - 16);
+
+    let glue = xor(&own_message[..16], victim_mac);
+    if let Some(i) = glue.iter().position(|b| b"&=;".contains(b)) {
+        return Err(CryptoError::InvalidCharacter {
+            position: victim_message.len() + i,
+            byte: glue[i],
+        });
+    }
+
+    Ok([&victim_message, &glue, &own_message[16..], own_mac].concat())
+}
@@ -0,0 +1,192 @@
+use num_bigint::{BigUint, RandBigInt};
+use num_integer::Integer;
+use num_traits::{One, Zero};
+use rand::Rng;
+
+const SMALL_PRIMES: [u32; 24] = [
+    3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97,
+];
+
+pub fn invmod(a: &BigUint, m: &BigUint) -> Option<BigUint> {
+    a.modinv(m)
+}
+
+pub fn is_probable_prime(n: &BigUint, rounds: usize) -> bool {
+    let two = BigUint::from(2u32);
+    if n < &two {
+        return false;
+    }
+
+    for p in SMALL_PRIMES.iter().map(|p| BigUint::from(*p)) {
+        if n == &p {
+            return true;
+        }
+        if (n % &p).is_zero() {
+            return false;
+        }
+    }
+    if n.is_even() {
+        return n == &two;
+    }
+
+    // write n - 1 as d * 2^s
+    let n_minus_one = n - 1u32;
+    let s = n_minus_one.trailing_zeros().unwrap();
+    let d = &n_minus_one >> s;
+
+    let mut rng = rand::thread_rng();
+    (0..rounds).all(|_| {
+        let a = rng.gen_biguint_range(&two, &n_minus_one);
+        let mut x = a.modpow(&d, n);
+        if x.is_one() || x == n_minus_one {
+            return true;
+        }
+
+        (1..s).any(|_| {
+            x = x.modpow(&two, n);
+            x == n_minus_one
+        })
+    })
+}
+
+pub fn gen_prime(bits: u64) -> BigUint {
+    let mut rng = rand::thread_rng();
+    loop {
+        // force the top two bits so that the product of two primes has the full size,
+        // and the bottom bit so that we only test odd numbers
+        let mut candidate = rng.gen_biguint(bits);
+        candidate.set_bit(bits - 1, true);
+        candidate.set_bit(bits - 2, true);
+        candidate.set_bit(0, true);
+
+        if is_probable_prime(&candidate, 32) {
+            break candidate;
+        }
+    }
+}
+
+// big endian, left padded with zeros to `len` bytes
+pub fn biguint_to_bytes(n: &BigUint, len: usize) -> Vec<u8> {
+    let bytes = n.to_bytes_be();
+    assert!(bytes.len() <= len || n.is_zero());
+    let mut padded = vec![0; len.saturating_sub(bytes.len())];
+    padded.extend(bytes);
+    padded.split_off(padded.len() - len)
+}
+
+// The distinct primes below `bound` that divide `n`, by trial division. Repeated
+// factors are only reported once.
+pub fn small_factors(n: &BigUint, bound: u64) -> Vec<u64> {
+    let mut n = n.clone();
+    let mut factors = Vec::new();
+    for d in 2..bound {
+        if (&n % d).is_zero() {
+            factors.push(d);
+            while (&n % d).is_zero() {
+                n /= d;
+            }
+        }
+    }
+    factors
+}
+
+// Combines x = r_i mod m_i for pairwise coprime moduli into x mod the product of
+// the moduli, returned as (x, product).
+pub fn crt(residues: &[(BigUint, BigUint)]) -> (BigUint, BigUint) {
+    let product: BigUint = residues.iter().map(|(_, m)| m).product();
+    let x = residues
+        .iter()
+        .map(|(r, m)| {
+            let rest = &product / m;
+            r * &rest * invmod(&rest, m).unwrap()
+        })
+        .sum::<BigUint>()
+        % &product;
+    (x, product)
+}
+
+// Tonelli-Shanks, a square root of n mod an odd prime p if there is one
+pub fn sqrt_mod(n: &BigUint, p: &BigUint) -> Option<BigUint> {
+    let n = n % p;
+    if n.is_zero() {
+        return Some(n);
+    }
+    let p_minus_one = p - 1u32;
+    // Euler's criterion, n^((p - 1) / 2) is 1 for squares
+    if !n.modpow(&(&p_minus_one >> 1), p).is_one() {
+        return None;
+    }
+
+    // p - 1 = q * 2^s with q odd
+    let s = p_minus_one.trailing_zeros().unwrap();
+    let q = &p_minus_one >> s;
+
+    // any non-residue will do
+    let mut z = BigUint::from(2u32);
+    while z.modpow(&(&p_minus_one >> 1), p) != p_minus_one {
+        z += 1u32;
+    }
+
+    let mut m = s;
+    let mut c = z.modpow(&q, p);
+    let mut t = n.modpow(&q, p);
+    let mut r = n.modpow(&((&q + 1u32) >> 1), p);
+    while !t.is_one() {
+        // the least i with t^(2^i) = 1
+        let mut i = 0;
+        let mut t2i = t.clone();
+        while !t2i.is_one() {
+            t2i = &t2i * &t2i % p;
+            i += 1;
+        }
+
+        let b = c.modpow(&(BigUint::one() << (m - i - 1)), p);
+        m = i;
+        c = &b * &b % p;
+        t = t * &c % p;
+        r = r * b % p;
+    }
+    Some(r)
+}
+
+// sieve of Eratosthenes
+pub fn primes_below(bound: u64) -> Vec<u64> {
+    let mut composite = vec![false; bound as usize];
+    let mut primes = Vec::new();
+    for n in 2..bound {
+        if !composite[n as usize] {
+            primes.push(n);
+            (n * n..bound)
+                .step_by(n as usize)
+                .for_each(|m| composite[m as usize] = true);
+        }
+    }
+    primes
+}
+
+// A prime p of at least `bits` bits where p - 1 is 2 times distinct primes below
+// `bound`, none of them from `avoid`. Returns p and the prime factors of p - 1.
+pub fn gen_smooth_prime(bits: u64, bound: u64, avoid: &[u64]) -> (BigUint, Vec<u64>) {
+    let mut rng = rand::thread_rng();
+    let primes: Vec<u64> = primes_below(bound)
+        .into_iter()
+        .filter(|r| *r > 2 && !avoid.contains(r))
+        .collect();
+
+    loop {
+        let mut factors = vec![2];
+        let mut n = BigUint::from(2u32);
+        while n.bits() < bits {
+            let r = primes[rng.gen_range(0..primes.len())];
+            if !factors.contains(&r) {
+                factors.push(r);
+                n *= r;
+            }
+        }
+
+        let p = n + 1u32;
+        if is_probable_prime(&p, 32) {
+            break (p, factors);
+        }
+    }
+}
@@ -0,0 +1,191 @@
+use num_bigint::{BigUint, RandBigInt};
+use num_integer::Integer;
+use num_traits::One;
+
+use super::{
+    bignum::{biguint_to_bytes, invmod},
+    rsa::{decrypt, key_len, RsaPrivateKey, RsaPublicKey},
+};
+
+// Which malformed paddings an oracle lets through, named as in Bardou et al.,
+// "Efficient Padding Oracle Attacks on Cryptographic Hardware". Each `true`
+// makes the oracle more permissive, and so cheaper to attack.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OracleStrictness {
+    // accept a zero byte within the first 8 bytes of padding
+    pub zero_in_padding: bool,
+    // accept a block with no zero separator after the padding
+    pub no_separator: bool,
+    // accept a message of any length, rather than only the expected one
+    pub wrong_length: bool,
+}
+
+impl OracleStrictness {
+    pub const FFF: OracleStrictness = OracleStrictness {
+        zero_in_padding: false,
+        no_separator: false,
+        wrong_length: false,
+    };
+    pub const FFT: OracleStrictness = OracleStrictness {
+        zero_in_padding: false,
+        no_separator: false,
+        wrong_length: true,
+    };
+    pub const TTT: OracleStrictness = OracleStrictness {
+        zero_in_padding: true,
+        no_separator: true,
+        wrong_length: true,
+    };
+}
+
+pub fn is_conforming(em: &[u8], strictness: OracleStrictness, message_len: usize) -> bool {
+    if em.len() < 11 || em[0] != 0 || em[1] != 2 {
+        return false;
+    }
+    if !strictness.zero_in_padding && em[2..10].contains(&0) {
+        return false;
+    }
+
+    match em[10..].iter().position(|b| *b == 0) {
+        None => strictness.no_separator,
+        Some(i) => strictness.wrong_length || em.len() - (10 + i + 1) == message_len,
+    }
+}
+
+// `message_len` is only checked by oracles that reject wrong lengths
+pub fn get_padding_oracle(
+    key: RsaPrivateKey,
+    strictness: OracleStrictness,
+    message_len: usize,
+) -> impl Fn(&BigUint) -> bool {
+    let k = key_len(&key.n);
+    move |ct: &BigUint| {
+        let em = biguint_to_bytes(&decrypt(&key, ct), k);
+        is_conforming(&em, strictness, message_len)
+    }
+}
+
+#[derive(Debug)]
+pub struct BleichenbacherResult {
+    pub plain_text: Vec<u8>,
+    pub queries: usize,
+}
+
+type Interval = (BigUint, BigUint);
+
+// union of the new intervals, kept sorted and non-overlapping
+fn insert_interval(intervals: &mut Vec<Interval>, (a, b): Interval) {
+    intervals.push((a, b));
+    intervals.sort();
+
+    let merged = intervals
+        .drain(..)
+        .fold(Vec::new(), |mut acc: Vec<Interval>, (a, b)| {
+            match acc.last_mut() {
+                Some(last) if a <= &last.1 + 1u32 => {
+                    if b > last.1 {
+                        last.1 = b;
+                    }
+                }
+                _ => acc.push((a, b)),
+            }
+            acc
+        });
+    *intervals = merged;
+}
+
+pub fn bleichenbacher_attack(
+    key: &RsaPublicKey,
+    ct: &BigUint,
+    oracle: impl Fn(&BigUint) -> bool,
+) -> BleichenbacherResult {
+    let n = &key.n;
+    let k = key_len(n);
+    let b = BigUint::one() << (8 * (k - 2));
+    let two_b = &b * 2u32;
+    let three_b = &b * 3u32;
+
+    let mut queries = 0;
+    let mut conforming = |c0: &BigUint, s: &BigUint| {
+        queries += 1;
+        oracle(&((c0 * s.modpow(&key.e, n)) % n))
+    };
+
+    // step 1: blinding. a valid PKCS#1 cipher text is already conforming, so s0 = 1
+    let mut rng = rand::thread_rng();
+    let mut s0 = BigUint::one();
+    while !conforming(ct, &s0) {
+        s0 = rng.gen_biguint_below(n);
+    }
+    let c0 = (ct * s0.modpow(&key.e, n)) % n;
+
+    let mut intervals: Vec<Interval> = vec![(two_b.clone(), &three_b - 1u32)];
+    let mut s = BigUint::one();
+    let mut i = 1;
+
+    loop {
+        if i == 1 {
+            // step 2a: smallest s >= n / 3B that gives a conforming message
+            s = n.div_ceil(&three_b);
+            while !conforming(&c0, &s) {
+                s += 1u32;
+            }
+        } else if intervals.len() > 1 {
+            // step 2b: more than one interval left, keep walking s upwards
+            s += 1u32;
+            while !conforming(&c0, &s) {
+                s += 1u32;
+            }
+        } else {
+            // step 2c: a single interval, search r and s in lock step which roughly
+            // halves the interval on every iteration
+            let (a, upper) = &intervals[0];
+            let mut r = (2u32 * (upper * &s - &two_b)).div_ceil(n);
+            s = 'search: loop {
+                let mut s_i = (&two_b + &r * n).div_ceil(upper);
+                let s_max = (&three_b + &r * n).div_ceil(a);
+                while s_i < s_max {
+                    if conforming(&c0, &s_i) {
+                        break 'search s_i;
+                    }
+                    s_i += 1u32;
+                }
+                r += 1u32;
+            };
+        }
+
+        // step 3: narrow the set of solutions
+        let mut narrowed = Vec::new();
+        for (a, upper) in &intervals {
+            let low = a * &s + 1u32;
+            let r_min = if low > three_b {
+                (low - &three_b).div_ceil(n)
+            } else {
+                BigUint::default()
+            };
+            let r_max = (upper * &s - &two_b) / n;
+
+            let mut r = r_min;
+            while r <= r_max {
+                let new_a = (&two_b + &r * n).div_ceil(&s).max(a.clone());
+                let new_b = ((&three_b - 1u32 + &r * n) / &s).min(upper.clone());
+                if new_a <= new_b {
+                    insert_interval(&mut narrowed, (new_a, new_b));
+                }
+                r += 1u32;
+            }
+        }
+        intervals = narrowed;
+
+        // step 4: a single value left is the (blinded) plain text
+        if intervals.len() == 1 && intervals[0].0 == intervals[0].1 {
+            let m = (&intervals[0].0 * invmod(&s0, n).unwrap()) % n;
+            break BleichenbacherResult {
+                plain_text: biguint_to_bytes(&m, k),
+                queries,
+            };
+        }
+
+        i += 1;
+    }
+}
@@ -1,25 +1,29 @@
-fn hex_char_to_byte(c: u8) -> u8 {
+use super::error::CryptoError;
+
+fn hex_char_to_byte(c: u8) -> Option<u8> {
     match c {
-        b'A'..=b'F' => c - b'A' + 10, // make 'A' => 11, B => 12 etc
-        b'a'..=b'f' => c - b'a' + 10,
-        b'0'..=b'9' => c - b'0', // make '0' => 0 etc
-        _ => panic!("Could not convert {:?}", c),
+        b'A'..=b'F' => Some(c - b'A' + 10), // make 'A' => 10, B => 11 etc
+        b'a'..=b'f' => Some(c - b'a' + 10),
+        b'0'..=b'9' => Some(c - b'0'), // make '0' => 0 etc
+        _ => None,
     }
 }
 
 #[must_use]
-pub fn hex_to_bytes<T: AsRef<[u8]>>(s: T) -> Result<Vec<u8>, &'static str> {
+pub fn hex_to_bytes<T: AsRef<[u8]>>(s: T) -> Result<Vec<u8>, CryptoError> {
     let hex = s.as_ref();
     if hex.len() % 2 != 0 {
-        return Err("bad length");
+        return Err(CryptoError::BadLength(hex.len()));
     }
-    hex.chunks(2)
-        .enumerate()
-        .map(|(_, pair)| {
-            let first_hex = hex_char_to_byte(pair[0]);
-            let second_hex = hex_char_to_byte(pair[1]);
-            return Ok(first_hex << 4 | second_hex);
+    let digit = |position: usize| {
+        hex_char_to_byte(hex[position]).ok_or(CryptoError::InvalidCharacter {
+            position,
+            byte: hex[position],
         })
+    };
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| Ok(digit(i)? << 4 | digit(i + 1)?))
         .collect()
 }
 
@@ -31,37 +35,151 @@ pub fn bytes_to_hex(s: Vec<u8>) -> String {
         .join("")
 }
 
-#[must_use]
-pub fn bytes_to_base64(bytes: &[u8]) -> String {
-    let ch = bytes
-        .chunks(3)
-        .map(|c| {
-            let a = c.get(0).unwrap_or(&0);
-            let b = c.get(1).unwrap_or(&0);
-            let c = c.get(2).unwrap_or(&0);
-            vec![
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Alphabet {
+    Standard,
+    // - and _ for + and /, RFC 4648 section 5
+    UrlSafe,
+}
+
+impl Alphabet {
+    fn chars(&self) -> &'static [u8; 64] {
+        match self {
+            Alphabet::Standard => {
+                b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/"
+            }
+            Alphabet::UrlSafe => {
+                b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_"
+            }
+        }
+    }
+
+    fn value(&self, c: u8) -> Option<u8> {
+        self.chars().iter().position(|x| *x == c).map(|i| i as u8)
+    }
+}
+
+// A flavour of base64. Strict decoding only takes exactly what encode would have
+// produced. Lenient decoding skips whitespace anywhere, as in files wrapped at 60
+// columns, doesn't mind missing padding, and ignores stray bits in the last
+// character.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Base64 {
+    pub alphabet: Alphabet,
+    pub padded: bool,
+    pub strict: bool,
+}
+
+impl Base64 {
+    pub const STANDARD: Base64 = Base64 {
+        alphabet: Alphabet::Standard,
+        padded: true,
+        strict: false,
+    };
+    pub const STANDARD_NO_PAD: Base64 = Base64 {
+        padded: false,
+        ..Base64::STANDARD
+    };
+    pub const URL_SAFE: Base64 = Base64 {
+        alphabet: Alphabet::UrlSafe,
+        ..Base64::STANDARD
+    };
+    pub const URL_SAFE_NO_PAD: Base64 = Base64 {
+        padded: false,
+        ..Base64::URL_SAFE
+    };
+
+    pub fn strict(self) -> Base64 {
+        Base64 {
+            strict: true,
+            ..self
+        }
+    }
+
+    pub fn encode(&self, bytes: &[u8]) -> String {
+        let chars = self.alphabet.chars();
+        let mut out = String::new();
+        for chunk in bytes.chunks(3) {
+            let a = chunk[0];
+            let b = chunk.get(1).copied().unwrap_or(0);
+            let c = chunk.get(2).copied().unwrap_or(0);
+            let sextets = [
                 a >> 2,
                 (a & 0b00000011) << 4 | b >> 4,
                 (b & 0b00001111) << 2 | c >> 6,
                 c & 0b00111111,
-            ]
-        })
-        .flat_map(encode_chunk);
+            ];
 
-    return String::from_iter(ch);
-}
+            // n bytes need n + 1 characters, the rest is padding
+            sextets[..chunk.len() + 1]
+                .iter()
+                .for_each(|x| out.push(chars[*x as usize] as char));
+            if self.padded {
+                (chunk.len()..3).for_each(|_| out.push('='));
+            }
+        }
+        out
+    }
+
+    pub fn decode<T: AsRef<[u8]>>(&self, s: T) -> Result<Vec<u8>, CryptoError> {
+        let input = s.as_ref();
+
+        // the values of the characters, and how much padding followed them
+        let mut sextets = Vec::with_capacity(input.len());
+        let mut padding = 0;
+        for (position, c) in input.iter().enumerate() {
+            if c.is_ascii_whitespace() && !self.strict {
+                continue;
+            }
+            if *c == b'=' {
+                padding += 1;
+                continue;
+            }
+            match self.alphabet.value(*c) {
+                Some(x) if padding == 0 => sextets.push(x),
+                Some(_) => return Err(CryptoError::InvalidPadding),
+                None => return Err(CryptoError::InvalidCharacter { position, byte: *c }),
+            }
+        }
 
-fn encode_chunk(chunk: Vec<u8>) -> Vec<char> {
-    let mut out = vec!['='; 4];
-    const BASE_CHARS: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+        let leftover = sextets.len() % 4;
+        if leftover == 1 {
+            return Err(CryptoError::BadLength(sextets.len()));
+        }
+        let expected_padding = if leftover == 0 { 0 } else { 4 - leftover };
+        let padding_ok = match (self.strict, self.padded) {
+            (true, true) => padding == expected_padding,
+            (true, false) => padding == 0,
+            (false, _) => padding == 0 || padding == expected_padding,
+        };
+        if !padding_ok {
+            return Err(CryptoError::InvalidPadding);
+        }
 
-    for i in 0..chunk.len() {
-        let x = chunk[i] as usize;
-        let chr = BASE_CHARS[x];
-        out[i] = chr as char;
+        let mut bytes = Vec::with_capacity(sextets.len() * 3 / 4);
+        for chunk in sextets.chunks(4) {
+            let bits =
+                chunk.iter().fold(0u32, |acc, x| acc << 6 | *x as u32) << (6 * (4 - chunk.len()));
+            let n = chunk.len() - 1;
+            bytes.extend(&bits.to_be_bytes()[1..1 + n]);
+
+            // bits of the last character that don't make it into a byte
+            if self.strict && n < 3 && bits & (0xffffff >> (8 * n)) != 0 {
+                return Err(CryptoError::InvalidPadding);
+            }
+        }
+        Ok(bytes)
     }
+}
+
+#[must_use]
+pub fn bytes_to_base64(bytes: &[u8]) -> String {
+    Base64::STANDARD.encode(bytes)
+}
 
-    out
+// leniently, so wrapped lines and all
+pub fn base64_to_bytes<T: AsRef<[u8]>>(s: T) -> Result<Vec<u8>, CryptoError> {
+    Base64::STANDARD.decode(s)
 }
 
 pub fn transpose(ct: &[u8], key_size: usize, offset: usize) -> Vec<u8> {
@@ -0,0 +1,165 @@
+use std::collections::HashMap;
+
+use miniz_oxide::deflate::compress_to_vec_zlib;
+use rand::{seq::SliceRandom, Rng};
+
+use super::aes::{encrypt_cbc, encrypt_ctr, pkcs7_padding};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Mode {
+    Ctr,
+    Cbc,
+}
+
+pub fn format_request(body: &[u8], session_id: &str) -> Vec<u8> {
+    let headers = format!(
+        "POST / HTTP/1.1\nHost: hapless.com\nCookie: sessionid={}\nContent-Length: {}\n",
+        session_id,
+        body.len()
+    );
+    [headers.as_bytes(), body].concat()
+}
+
+// compresses then encrypts the request under a fresh key, only leaking the length
+pub fn get_compression_oracle(mode: Mode, session_id: &str) -> impl Fn(&[u8]) -> usize {
+    let session_id = session_id.to_owned();
+
+    move |body: &[u8]| {
+        let mut rng = rand::thread_rng();
+        let key: [u8; 16] = rng.gen();
+        let compressed = compress_to_vec_zlib(&format_request(body, &session_id), 6);
+
+        let ciphertext = match mode {
+            Mode::Ctr => encrypt_ctr(&key, compressed, rng.gen()),
+            Mode::Cbc => {
+                let iv: [u8; 16] = rng.gen();
+                encrypt_cbc(&key, pkcs7_padding(&compressed, 16), &iv)
+            }
+        };
+        ciphertext
+            .expect("a 16 byte key and IV, and padded blocks")
+            .len()
+    }
+}
+
+#[derive(Debug)]
+pub struct CrimeResult {
+    // as far as it could be told for certain
+    pub secret: String,
+    // the candidates for the next character if no attempt could tell them apart,
+    // which leaves the secret cut short; empty when it was read to the end
+    pub tied: Vec<u8>,
+}
+
+const SESSION_ALPHABET: &[u8] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/=\n";
+// characters that appear nowhere in the request, so they never match against it
+const JUNK: &[u8] = b"!@#$%^&*()[]<>~|";
+const SEPARATOR: &[u8] = b"{}";
+
+const MAX_SECRET_LEN: usize = 128;
+const MAX_PADDING: usize = 64;
+const ATTEMPTS: usize = 8;
+
+// Scores each guess at increasing amounts of junk padding, and returns the
+// guesses with the most votes across the paddings.
+//
+// The right guess extends an existing match and compresses a little better, but
+// Huffman coding makes some characters cheaper than others regardless. So each
+// guess is scored against the same characters with a separator breaking the match
+// ("two tries"), which costs the same apart from the match. The difference is a
+// few bits at most and gets rounded away unless the request sits right on a byte,
+// or for CBC a block, boundary; the junk shifts it around until it does.
+//
+// Every padding that tells the guesses apart shares one vote between the few
+// whose score differs from the rest. That is usually the right guess coming out
+// better, but at some positions a lazy match makes it come out worse just as
+// consistently; either way it keeps standing out, where a wrong guess that
+// happens to cross a boundary only does so now and then. So no single padding
+// decides, only the tally.
+fn best_guesses(
+    oracle: &impl Fn(&[u8]) -> usize,
+    junk: &[u8],
+    recovered: &[u8],
+    guesses: &[u8],
+) -> Vec<u8> {
+    let mut votes = vec![0.0; guesses.len()];
+    for padding in 0..junk.len() {
+        let prefix = [&junk[..padding], recovered].concat();
+        let scores = guesses
+            .iter()
+            .map(|guess| {
+                let hit = oracle(&[prefix.as_slice(), &[*guess], SEPARATOR].concat());
+                let miss = oracle(&[prefix.as_slice(), SEPARATOR, &[*guess]].concat());
+                hit as isize - miss as isize
+            })
+            .collect::<Vec<isize>>();
+
+        let mut counts = HashMap::new();
+        scores
+            .iter()
+            .for_each(|x| *counts.entry(*x).or_insert(0) += 1);
+        let usual = *counts.iter().max_by_key(|(_, n)| **n).unwrap().0;
+        let odd = (0..guesses.len())
+            .filter(|i| scores[*i] != usual)
+            .collect::<Vec<usize>>();
+        if odd.is_empty() || 2 * odd.len() >= guesses.len() {
+            continue;
+        }
+        odd.iter().for_each(|i| votes[*i] += 1.0 / odd.len() as f64);
+
+        // done once the leader is further ahead than one padding could undo
+        let mut sorted = votes.clone();
+        sorted.sort_by(|a, b| b.partial_cmp(a).unwrap());
+        if sorted.len() < 2 || sorted[0] - sorted[1] > 1.0 {
+            break;
+        }
+    }
+
+    let most = votes.iter().cloned().fold(0.0, f64::max);
+    guesses
+        .iter()
+        .zip(&votes)
+        .filter(|(_, v)| **v == most)
+        .map(|(guess, _)| *guess)
+        .collect()
+}
+
+// Recovers the cookie following `known` one character at a time, stopping at the
+// end of its line. The junk is random characters from a small alphabet, so it still
+// compresses a bit; incompressible junk tips the compressor into storing the
+// request as is, which leaks nothing. When no amount of it separates the
+// candidates, fresh junk is drawn and tried again, and after ATTEMPTS of that it
+// gives up rather than guess.
+pub fn recover_secret(oracle: impl Fn(&[u8]) -> usize, known: &str) -> CrimeResult {
+    let mut rng = rand::thread_rng();
+
+    let mut recovered = known.as_bytes().to_vec();
+    let mut tied = Vec::new();
+
+    while recovered.len() < known.len() + MAX_SECRET_LEN {
+        for _ in 0..ATTEMPTS {
+            let junk: Vec<u8> = (0..MAX_PADDING)
+                .map(|_| *JUNK.choose(&mut rng).unwrap())
+                .collect();
+            tied = best_guesses(&oracle, &junk, &recovered, SESSION_ALPHABET);
+            if tied.len() == 1 {
+                break;
+            }
+        }
+
+        let [c] = tied[..] else {
+            break;
+        };
+        tied.clear();
+        if c == b'\n' {
+            break;
+        }
+        recovered.push(c);
+    }
+
+    CrimeResult {
+        secret: String::from_utf8(recovered[known.len()..].to_vec()).unwrap(),
+        tied,
+    }
+}
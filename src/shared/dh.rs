@@ -0,0 +1,66 @@
+use num_bigint::{BigUint, RandBigInt};
+
+use super::mac::hmac_sha256;
+
+// g generates a subgroup of prime order q in Z_p*
+#[derive(Debug, Clone)]
+pub struct DhParams {
+    pub p: BigUint,
+    pub g: BigUint,
+    pub q: BigUint,
+}
+
+impl DhParams {
+    pub fn new(p: &str, g: &str, q: &str) -> DhParams {
+        let parse = |n: &str| BigUint::parse_bytes(n.as_bytes(), 10).unwrap();
+        DhParams {
+            p: parse(p),
+            g: parse(g),
+            q: parse(q),
+        }
+    }
+
+    // the group from challenge 57, where (p - 1) / q is full of small factors
+    pub fn challenge_57() -> DhParams {
+        DhParams::new(
+            "7199773997391911030609999317773941274322764333428698921736339643928346453700085358802973900485592910475480089726140708102474957429903531369589969318716771",
+            "4565356397095740655436854503483826832136106141639563487732438195343690437606117828318042418238184896212352329118608100083187535033402010599512641674644143",
+            "236234353446506858198510045061214171961",
+        )
+    }
+
+    // (p - 1) / q has small factors again, but too few of them to cover q
+    pub fn challenge_58() -> DhParams {
+        DhParams::new(
+            "11470374874925275658116663507232161402086650258453896274534991676898999262641581519101074740642369848233294239851519212341844337347119899874391456329785623",
+            "622952335333961296978159266084741085889881358738459939978290179936063635566740258555167783009058567397963466103140082647486611657350811560630587013183357",
+            "335062023296420808191071248367701059461",
+        )
+    }
+}
+
+// (secret, public)
+pub fn generate_keypair(params: &DhParams) -> (BigUint, BigUint) {
+    let secret = rand::thread_rng().gen_biguint_range(&BigUint::from(1u32), &params.q);
+    let public = params.g.modpow(&secret, &params.p);
+    (secret, public)
+}
+
+pub fn shared_secret(params: &DhParams, secret: &BigUint, public: &BigUint) -> BigUint {
+    public.modpow(secret, &params.p)
+}
+
+// Bob takes any public key at all, and replies with his message and its MAC under
+// the shared secret
+pub fn get_mac_oracle(
+    params: DhParams,
+    secret: BigUint,
+    message: &[u8],
+) -> impl Fn(&BigUint) -> (Vec<u8>, Vec<u8>) {
+    let message = message.to_vec();
+    move |public: &BigUint| {
+        let k = shared_secret(&params, &secret, public);
+        let tag = hmac_sha256(&k.to_bytes_be(), &message);
+        (message.clone(), tag)
+    }
+}
@@ -0,0 +1,204 @@
+use std::collections::HashMap;
+
+use num_bigint::BigUint;
+use num_traits::{CheckedSub, One, ToPrimitive, Zero};
+
+use super::bignum::{crt, invmod};
+
+// Baby-step giant-step for y = g^x with x in [a, b]. Takes about sqrt(b - a)
+// multiplications, but has to keep as many elements around.
+pub fn bsgs(g: &BigUint, y: &BigUint, a: &BigUint, b: &BigUint, p: &BigUint) -> Option<BigUint> {
+    let m = (b - a).sqrt() + 1u32;
+
+    // g^j => j for the baby steps
+    let mut baby: HashMap<BigUint, BigUint> = HashMap::new();
+    let mut x = BigUint::one();
+    let mut j = BigUint::zero();
+    while j < m {
+        baby.entry(x.clone()).or_insert_with(|| j.clone());
+        x = x * g % p;
+        j += 1u32;
+    }
+
+    // y * g^-a * (g^-m)^i, for i giant steps
+    let giant = invmod(&g.modpow(&m, p), p)?;
+    let mut x = y * invmod(&g.modpow(a, p), p)? % p;
+    let mut i = BigUint::zero();
+    while i <= m {
+        if let Some(j) = baby.get(&x) {
+            let found = a + &i * &m + j;
+            return (&found <= b).then_some(found);
+        }
+        x = x * &giant % p;
+        i += 1u32;
+    }
+    None
+}
+
+// The jump function for the kangaroos: an element jumps by the size at its index,
+// which is picked by the element's low bits.
+#[derive(Debug, Clone)]
+pub struct Jumps {
+    pub sizes: Vec<u64>,
+}
+
+impl Jumps {
+    // 1, 2, 4, ... 2^(k-1), the choice from Pollard's paper
+    pub fn powers_of_two(k: u32) -> Jumps {
+        Jumps {
+            sizes: (0..k).map(|i| 1 << i).collect(),
+        }
+    }
+
+    // the fewest powers of two with a mean of at least half the square root of the
+    // width of the interval, which balances the work of the two kangaroos
+    pub fn for_interval(a: &BigUint, b: &BigUint) -> Jumps {
+        let target = ((b - a).sqrt() / 2u32).to_u64().unwrap_or(u64::MAX);
+        (1..64)
+            .map(Jumps::powers_of_two)
+            .find(|jumps| jumps.mean() >= target)
+            .unwrap_or_else(|| Jumps::powers_of_two(63))
+    }
+
+    pub fn index(&self, fingerprint: u64) -> usize {
+        (fingerprint % self.sizes.len() as u64) as usize
+    }
+
+    pub fn mean(&self) -> u64 {
+        self.sizes.iter().sum::<u64>() / self.sizes.len() as u64
+    }
+}
+
+// What the kangaroos need from a group, written multiplicatively
+pub trait Group {
+    type Element: Clone + PartialEq;
+
+    fn op(&self, x: &Self::Element, y: &Self::Element) -> Self::Element;
+    fn pow(&self, x: &Self::Element, k: &BigUint) -> Self::Element;
+    // a few bits of the element, which pick the size of its jump
+    fn fingerprint(&self, x: &Self::Element) -> u64;
+}
+
+// Z_p* for the given p
+pub struct ModP<'a>(pub &'a BigUint);
+
+impl Group for ModP<'_> {
+    type Element = BigUint;
+
+    fn op(&self, x: &BigUint, y: &BigUint) -> BigUint {
+        x * y % self.0
+    }
+
+    fn pow(&self, x: &BigUint, k: &BigUint) -> BigUint {
+        x.modpow(k, self.0)
+    }
+
+    fn fingerprint(&self, x: &BigUint) -> u64 {
+        x.iter_u64_digits().next().unwrap_or(0)
+    }
+}
+
+// Pollard's kangaroo (lambda) method for y = g^x with x in [a, b]. A tame kangaroo
+// starts at g^b and leaves a trap where it stops. A wild one starts at y and, being
+// driven by the same jump function, falls into step with the tame one if it ever
+// lands on any of its footprints. About sqrt(b - a) group operations and no memory
+// worth mentioning, but it can miss, which is reported as None.
+pub fn kangaroo_in<G: Group>(
+    group: &G,
+    g: &G::Element,
+    y: &G::Element,
+    a: &BigUint,
+    b: &BigUint,
+    jumps: &Jumps,
+) -> Option<BigUint> {
+    let steps: Vec<G::Element> = jumps
+        .sizes
+        .iter()
+        .map(|s| group.pow(g, &(*s).into()))
+        .collect();
+
+    let mut tame_distance = BigUint::zero();
+    let mut tame = group.pow(g, b);
+    for _ in 0..4 * jumps.mean() {
+        let i = jumps.index(group.fingerprint(&tame));
+        tame_distance += jumps.sizes[i];
+        tame = group.op(&tame, &steps[i]);
+    }
+
+    let limit = b - a + &tame_distance;
+    let mut wild_distance = BigUint::zero();
+    let mut wild = y.clone();
+    while wild_distance <= limit {
+        if wild == tame {
+            return (b + tame_distance).checked_sub(&wild_distance);
+        }
+        let i = jumps.index(group.fingerprint(&wild));
+        wild_distance += jumps.sizes[i];
+        wild = group.op(&wild, &steps[i]);
+    }
+    None
+}
+
+pub fn kangaroo(
+    g: &BigUint,
+    y: &BigUint,
+    a: &BigUint,
+    b: &BigUint,
+    p: &BigUint,
+    jumps: &Jumps,
+) -> Option<BigUint> {
+    kangaroo_in(&ModP(p), g, y, a, b, jumps)
+}
+
+// The kangaroo with jumps picked for the interval. A miss is down to the jump
+// function rather than the target, so try again with a couple of larger ones.
+pub fn dlog_interval_in<G: Group>(
+    group: &G,
+    g: &G::Element,
+    y: &G::Element,
+    a: &BigUint,
+    b: &BigUint,
+) -> Option<BigUint> {
+    let k = Jumps::for_interval(a, b).sizes.len() as u32;
+    (k..k + 3).find_map(|k| kangaroo_in(group, g, y, a, b, &Jumps::powers_of_two(k)))
+}
+
+pub fn dlog_interval(
+    g: &BigUint,
+    y: &BigUint,
+    a: &BigUint,
+    b: &BigUint,
+    p: &BigUint,
+) -> Option<BigUint> {
+    dlog_interval_in(&ModP(p), g, y, a, b)
+}
+
+// whether g generates all of Z_p*, given the distinct prime factors of p - 1
+pub fn is_generator(g: &BigUint, p: &BigUint, factors: &[u64]) -> bool {
+    let order = p - 1u32;
+    factors
+        .iter()
+        .all(|r| !g.modpow(&(&order / *r), p).is_one())
+}
+
+// Pohlig-Hellman for y = g^x mod p, when p - 1 is the product of the distinct small
+// primes `factors`: the log in each subgroup of order r by baby-step giant-step,
+// then the CRT. The answer is mod the order of g.
+pub fn pohlig_hellman(g: &BigUint, y: &BigUint, p: &BigUint, factors: &[u64]) -> Option<BigUint> {
+    let order = p - 1u32;
+    let residues = factors
+        .iter()
+        .map(|r| {
+            let e = &order / *r;
+            let x = bsgs(
+                &g.modpow(&e, p),
+                &y.modpow(&e, p),
+                &BigUint::zero(),
+                &BigUint::from(r - 1),
+                p,
+            )?;
+            Some((x, BigUint::from(*r)))
+        })
+        .collect::<Option<Vec<(BigUint, BigUint)>>>()?;
+    Some(crt(&residues).0)
+}
@@ -0,0 +1,100 @@
+use num_bigint::{BigUint, RandBigInt};
+use num_integer::Integer;
+use num_traits::One;
+
+use super::{
+    bignum::{crt, gen_smooth_prime, invmod},
+    dlog::{is_generator, pohlig_hellman},
+    ec::{EcParams, Point},
+    ecdsa::{hash_message, Signature},
+    rsa::{key_len, pkcs1_sign_pad, RsaPrivateKey, RsaPublicKey},
+};
+
+// ECDSA never checks where the generator came from. Verification computes
+// R = u1 * G + u2 * Q, so for any d' the generator G' = (u1 + u2 * d')^-1 * R and
+// public key Q' = d' * G' produce the same R. Returns the new parameters, d' and Q'.
+pub fn ecdsa_dsks(
+    params: &EcParams,
+    public: &Point,
+    message: &[u8],
+    signature: &Signature,
+) -> (EcParams, BigUint, Point) {
+    let curve = &params.curve;
+    let q = &params.q;
+
+    let w = invmod(&signature.s, q).unwrap();
+    let u1 = hash_message(message, q) * &w % q;
+    let u2 = &signature.r * &w % q;
+    let r = curve.add(
+        &curve.scalar_mul(&params.g, &u1),
+        &curve.scalar_mul(public, &u2),
+    );
+
+    let mut rng = rand::thread_rng();
+    loop {
+        let secret = rng.gen_biguint_range(&BigUint::one(), q);
+        let Some(t_inv) = invmod(&((&u1 + &u2 * &secret) % q), q) else {
+            continue;
+        };
+
+        let g = curve.scalar_mul(&r, &t_inv);
+        let public = curve.scalar_mul(&g, &secret);
+        break (
+            EcParams {
+                curve: curve.clone(),
+                g,
+                q: q.clone(),
+            },
+            secret,
+            public,
+        );
+    }
+}
+
+// Finds a key pair under which `signature` is a valid signature of `message`.
+// With p - 1 and q - 1 smooth and s generating both Z_p* and Z_q*, the padded
+// message is a power of s mod each prime, and Pohlig-Hellman finds which. The two
+// exponents agree mod 2 half the time, and CRT into e' mod lcm(p - 1, q - 1); keep
+// going until e' is invertible so there is a private key too.
+pub fn rsa_dsks(
+    public: &RsaPublicKey,
+    message: &[u8],
+    signature: &BigUint,
+) -> (RsaPublicKey, RsaPrivateKey) {
+    // a little larger than n, so that the signature is less than the new modulus
+    let bits = public.n.bits() / 2 + 1;
+    let bound = 1 << 16;
+
+    loop {
+        let (p, p_factors) = gen_smooth_prime(bits, bound, &[]);
+        if !is_generator(signature, &p, &p_factors) {
+            continue;
+        }
+        let (q, q_factors) = gen_smooth_prime(bits, bound, &p_factors[1..]);
+        if !is_generator(signature, &q, &q_factors) {
+            continue;
+        }
+
+        let n = &p * &q;
+        let em = BigUint::from_bytes_be(&pkcs1_sign_pad(message, key_len(&n)));
+        let (Some(ep), Some(eq)) = (
+            pohlig_hellman(signature, &em, &p, &p_factors),
+            pohlig_hellman(signature, &em, &q, &q_factors),
+        ) else {
+            continue;
+        };
+        if ep.is_even() != eq.is_even() {
+            continue;
+        }
+
+        // p - 1 and q - 1 only share the 2, which the parity check took care of
+        let p_order = &p - 1u32;
+        let q_half = (&q - 1u32) / 2u32;
+        let (e, _) = crt(&[(ep, p_order.clone()), (eq % &q_half, q_half)]);
+
+        let lambda = p_order.lcm(&(&q - 1u32));
+        if let Some(d) = invmod(&e, &lambda) {
+            break (RsaPublicKey { e, n: n.clone() }, RsaPrivateKey { d, n });
+        }
+    }
+}
@@ -0,0 +1,321 @@
+use num_bigint::{BigUint, RandBigInt};
+use num_traits::{One, Zero};
+
+use super::{
+    bignum::{invmod, sqrt_mod},
+    dlog::Group,
+    mac::hmac_sha256,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Point {
+    Infinity,
+    Affine(BigUint, BigUint),
+}
+
+// Jacobian coordinates, (X, Y, Z) is the affine point (X / Z^2, Y / Z^3). Adding
+// and doubling need no inversions, so scalar multiplication only pays for one at
+// the very end.
+#[derive(Debug, Clone)]
+pub struct ProjectivePoint {
+    pub x: BigUint,
+    pub y: BigUint,
+    pub z: BigUint,
+}
+
+// y^2 = x^3 + ax + b over GF(p)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Curve {
+    pub a: BigUint,
+    pub b: BigUint,
+    pub p: BigUint,
+}
+
+impl Curve {
+    pub fn new(a: &BigUint, b: &BigUint, p: &BigUint) -> Curve {
+        Curve {
+            a: a % p,
+            b: b % p,
+            p: p.clone(),
+        }
+    }
+
+    fn sub(&self, x: &BigUint, y: &BigUint) -> BigUint {
+        (x + &self.p - y % &self.p) % &self.p
+    }
+
+    fn mul(&self, x: &BigUint, y: &BigUint) -> BigUint {
+        x * y % &self.p
+    }
+
+    // x^3 + ax + b, the square y has to be
+    pub fn rhs(&self, x: &BigUint) -> BigUint {
+        (x.modpow(&BigUint::from(3u32), &self.p) + &self.a * x + &self.b) % &self.p
+    }
+
+    pub fn contains(&self, point: &Point) -> bool {
+        match point {
+            Point::Infinity => true,
+            Point::Affine(x, y) => self.mul(y, y) == self.rhs(x),
+        }
+    }
+
+    pub fn negate(&self, point: &Point) -> Point {
+        match point {
+            Point::Infinity => Point::Infinity,
+            Point::Affine(x, y) => Point::Affine(x.clone(), self.sub(&BigUint::zero(), y)),
+        }
+    }
+
+    // Neither this nor the projective arithmetic ever looks at b, so they will
+    // happily work on points from any curve that shares a.
+    pub fn add(&self, p1: &Point, p2: &Point) -> Point {
+        let (x1, y1, x2, y2) = match (p1, p2) {
+            (Point::Infinity, _) => return p2.clone(),
+            (_, Point::Infinity) => return p1.clone(),
+            (Point::Affine(x1, y1), Point::Affine(x2, y2)) => (x1, y1, x2, y2),
+        };
+        if x1 == x2 && (y1 + y2) % &self.p == BigUint::zero() {
+            return Point::Infinity;
+        }
+
+        let slope = if x1 == x2 {
+            let numerator = 3u32 * self.mul(x1, x1) + &self.a;
+            let denominator = 2u32 * y1;
+            self.mul(&numerator, &invmod(&denominator, &self.p).unwrap())
+        } else {
+            let numerator = self.sub(y2, y1);
+            let denominator = self.sub(x2, x1);
+            self.mul(&numerator, &invmod(&denominator, &self.p).unwrap())
+        };
+
+        let x3 = self.sub(&self.sub(&self.mul(&slope, &slope), x1), x2);
+        let y3 = self.sub(&self.mul(&slope, &self.sub(x1, &x3)), y1);
+        Point::Affine(x3, y3)
+    }
+
+    // plain double-and-add on affine points, an inversion for every step
+    pub fn scalar_mul_affine(&self, point: &Point, k: &BigUint) -> Point {
+        (0..k.bits()).rev().fold(Point::Infinity, |acc, i| {
+            let acc = self.add(&acc, &acc);
+            if k.bit(i) {
+                self.add(&acc, point)
+            } else {
+                acc
+            }
+        })
+    }
+
+    pub fn to_projective(&self, point: &Point) -> ProjectivePoint {
+        match point {
+            Point::Infinity => ProjectivePoint {
+                x: BigUint::one(),
+                y: BigUint::one(),
+                z: BigUint::zero(),
+            },
+            Point::Affine(x, y) => ProjectivePoint {
+                x: x.clone(),
+                y: y.clone(),
+                z: BigUint::one(),
+            },
+        }
+    }
+
+    pub fn to_affine(&self, point: &ProjectivePoint) -> Point {
+        if point.z.is_zero() {
+            return Point::Infinity;
+        }
+        let z_inv = invmod(&point.z, &self.p).unwrap();
+        let z_inv2 = self.mul(&z_inv, &z_inv);
+        Point::Affine(
+            self.mul(&point.x, &z_inv2),
+            self.mul(&point.y, &self.mul(&z_inv2, &z_inv)),
+        )
+    }
+
+    pub fn double_projective(&self, point: &ProjectivePoint) -> ProjectivePoint {
+        let ProjectivePoint { x, y, z } = point;
+        if z.is_zero() || y.is_zero() {
+            return self.to_projective(&Point::Infinity);
+        }
+
+        let y2 = self.mul(y, y);
+        let s = self.mul(&(4u32 * x), &y2);
+        let z2 = self.mul(z, z);
+        let m = (3u32 * self.mul(x, x) + self.mul(&self.a, &self.mul(&z2, &z2))) % &self.p;
+
+        let x3 = self.sub(&self.mul(&m, &m), &(2u32 * &s));
+        let y3 = self.sub(
+            &self.mul(&m, &self.sub(&s, &x3)),
+            &(8u32 * self.mul(&y2, &y2)),
+        );
+        let z3 = self.mul(&(2u32 * y), z);
+        ProjectivePoint {
+            x: x3,
+            y: y3,
+            z: z3,
+        }
+    }
+
+    pub fn add_projective(&self, p1: &ProjectivePoint, p2: &ProjectivePoint) -> ProjectivePoint {
+        if p1.z.is_zero() {
+            return p2.clone();
+        }
+        if p2.z.is_zero() {
+            return p1.clone();
+        }
+
+        let z1z1 = self.mul(&p1.z, &p1.z);
+        let z2z2 = self.mul(&p2.z, &p2.z);
+        let u1 = self.mul(&p1.x, &z2z2);
+        let u2 = self.mul(&p2.x, &z1z1);
+        let s1 = self.mul(&p1.y, &self.mul(&p2.z, &z2z2));
+        let s2 = self.mul(&p2.y, &self.mul(&p1.z, &z1z1));
+
+        if u1 == u2 {
+            return if s1 == s2 {
+                self.double_projective(p1)
+            } else {
+                self.to_projective(&Point::Infinity)
+            };
+        }
+
+        let h = self.sub(&u2, &u1);
+        let r = self.sub(&s2, &s1);
+        let h2 = self.mul(&h, &h);
+        let h3 = self.mul(&h2, &h);
+        let u1h2 = self.mul(&u1, &h2);
+
+        let x3 = self.sub(&self.sub(&self.mul(&r, &r), &h3), &(2u32 * &u1h2));
+        let y3 = self.sub(&self.mul(&r, &self.sub(&u1h2, &x3)), &self.mul(&s1, &h3));
+        let z3 = self.mul(&h, &self.mul(&p1.z, &p2.z));
+        ProjectivePoint {
+            x: x3,
+            y: y3,
+            z: z3,
+        }
+    }
+
+    pub fn scalar_mul(&self, point: &Point, k: &BigUint) -> Point {
+        let point = self.to_projective(point);
+        let result = (0..k.bits())
+            .rev()
+            .fold(self.to_projective(&Point::Infinity), |acc, i| {
+                let acc = self.double_projective(&acc);
+                if k.bit(i) {
+                    self.add_projective(&acc, &point)
+                } else {
+                    acc
+                }
+            });
+        self.to_affine(&result)
+    }
+
+    // a uniformly random point, which need not be in any particular subgroup
+    pub fn random_point(&self) -> Point {
+        let mut rng = rand::thread_rng();
+        loop {
+            let x = rng.gen_biguint_below(&self.p);
+            if let Some(y) = sqrt_mod(&self.rhs(&x), &self.p) {
+                break Point::Affine(x, y);
+            }
+        }
+    }
+}
+
+// the points under addition, so the kangaroo can hop about on the curve
+impl Group for Curve {
+    type Element = Point;
+
+    fn op(&self, x: &Point, y: &Point) -> Point {
+        self.add(x, y)
+    }
+
+    fn pow(&self, x: &Point, k: &BigUint) -> Point {
+        self.scalar_mul(x, k)
+    }
+
+    fn fingerprint(&self, x: &Point) -> u64 {
+        match x {
+            Point::Infinity => 0,
+            Point::Affine(x, _) => x.iter_u64_digits().next().unwrap_or(0),
+        }
+    }
+}
+
+// the base point g has prime order q
+#[derive(Debug, Clone)]
+pub struct EcParams {
+    pub curve: Curve,
+    pub g: Point,
+    pub q: BigUint,
+}
+
+fn parse(n: &str) -> BigUint {
+    BigUint::parse_bytes(n.as_bytes(), 10).unwrap()
+}
+
+impl EcParams {
+    // y^2 = x^3 - 95051x + 11279326, from challenge 59
+    pub fn cryptopals() -> EcParams {
+        let p = parse("233970423115425145524320034830162017933");
+        EcParams {
+            curve: Curve::new(&(&p - 95051u32), &BigUint::from(11279326u32), &p),
+            g: Point::Affine(
+                BigUint::from(182u32),
+                parse("85518893674295321206118380980485522083"),
+            ),
+            q: parse("29246302889428143187362802287225875743"),
+        }
+    }
+}
+
+// Curves sharing a with the cryptopals curve, and their orders, which have plenty
+// of small factors
+pub fn invalid_curves(params: &EcParams) -> Vec<(Curve, BigUint)> {
+    [
+        (210u32, "233970423115425145550826547352470124412"),
+        (504, "233970423115425145544350131142039591210"),
+        (727, "233970423115425145545378039958152057148"),
+    ]
+    .iter()
+    .map(|(b, order)| {
+        let curve = Curve::new(&params.curve.a, &BigUint::from(*b), &params.curve.p);
+        (curve, parse(order))
+    })
+    .collect()
+}
+
+// (secret, public)
+pub fn generate_keypair(params: &EcParams) -> (BigUint, Point) {
+    let secret = rand::thread_rng().gen_biguint_range(&BigUint::one(), &params.q);
+    let public = params.curve.scalar_mul(&params.g, &secret);
+    (secret, public)
+}
+
+pub fn shared_secret(params: &EcParams, secret: &BigUint, public: &Point) -> Point {
+    params.curve.scalar_mul(public, secret)
+}
+
+// both coordinates, so that a point and its negation give different keys
+pub fn point_to_bytes(point: &Point) -> Vec<u8> {
+    match point {
+        Point::Infinity => Vec::new(),
+        Point::Affine(x, y) => [x.to_bytes_be(), y.to_bytes_be()].concat(),
+    }
+}
+
+// Bob multiplies whatever point he is sent by his secret without checking that it
+// is on the curve, and replies with his message and its MAC under the result
+pub fn get_ecdh_mac_oracle(
+    params: EcParams,
+    secret: BigUint,
+    message: &[u8],
+) -> impl Fn(&Point) -> (Vec<u8>, Vec<u8>) {
+    let message = message.to_vec();
+    move |public: &Point| {
+        let k = shared_secret(&params, &secret, public);
+        let tag = hmac_sha256(&point_to_bytes(&k), &message);
+        (message.clone(), tag)
+    }
+}
@@ -0,0 +1,67 @@
+use num_bigint::{BigUint, RandBigInt};
+use num_traits::{One, Zero};
+
+use super::{
+    bignum::invmod,
+    ec::{EcParams, Point},
+    sha256::sha256,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Signature {
+    pub r: BigUint,
+    pub s: BigUint,
+}
+
+// the leftmost bits of SHA-256(message), as many as q has
+pub fn hash_message(message: &[u8], q: &BigUint) -> BigUint {
+    let h = BigUint::from_bytes_be(&sha256(message));
+    h >> 256u64.saturating_sub(q.bits())
+}
+
+fn x_mod_q(point: &Point, q: &BigUint) -> Option<BigUint> {
+    match point {
+        Point::Infinity => None,
+        Point::Affine(x, _) => Some(x % q),
+    }
+}
+
+// with the nonce used, for when that matters
+pub fn sign_with_nonce(
+    params: &EcParams,
+    secret: &BigUint,
+    message: &[u8],
+    k: &BigUint,
+) -> Option<Signature> {
+    let q = &params.q;
+    let r = x_mod_q(&params.curve.scalar_mul(&params.g, k), q)?;
+    let s = invmod(k, q)? * (hash_message(message, q) + &r * secret) % q;
+    (!r.is_zero() && !s.is_zero()).then_some(Signature { r, s })
+}
+
+pub fn sign(params: &EcParams, secret: &BigUint, message: &[u8]) -> Signature {
+    let mut rng = rand::thread_rng();
+    loop {
+        let k = rng.gen_biguint_range(&BigUint::one(), &params.q);
+        if let Some(signature) = sign_with_nonce(params, secret, message, &k) {
+            break signature;
+        }
+    }
+}
+
+pub fn verify(params: &EcParams, public: &Point, message: &[u8], signature: &Signature) -> bool {
+    let q = &params.q;
+    let Signature { r, s } = signature;
+    if r.is_zero() || r >= q || s.is_zero() || s >= q {
+        return false;
+    }
+
+    let w = invmod(s, q).unwrap();
+    let u1 = hash_message(message, q) * &w % q;
+    let u2 = r * &w % q;
+    let point = params.curve.add(
+        &params.curve.scalar_mul(&params.g, &u1),
+        &params.curve.scalar_mul(public, &u2),
+    );
+    x_mod_q(&point, q).as_ref() == Some(r)
+}
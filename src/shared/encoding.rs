@@ -0,0 +1,328 @@
+use super::{
+    conversion::{bytes_to_hex, hex_to_bytes, Base64},
+    error::CryptoError,
+};
+
+// The ways bytes turn up in the wild
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Encoding {
+    Hex,
+    Base64,
+    Base64Url,
+    Base32,
+    Ascii85,
+    // [72, 105], or just whitespace or commas between the numbers
+    Decimal,
+    Raw,
+}
+
+pub const ENCODINGS: [Encoding; 7] = [
+    Encoding::Hex,
+    Encoding::Base64,
+    Encoding::Base64Url,
+    Encoding::Base32,
+    Encoding::Ascii85,
+    Encoding::Decimal,
+    Encoding::Raw,
+];
+
+impl Encoding {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Encoding::Hex => "hex",
+            Encoding::Base64 => "base64",
+            Encoding::Base64Url => "base64url",
+            Encoding::Base32 => "base32",
+            Encoding::Ascii85 => "ascii85",
+            Encoding::Decimal => "decimal",
+            Encoding::Raw => "raw",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Encoding> {
+        ENCODINGS.into_iter().find(|e| e.name() == name)
+    }
+
+    pub fn encode(&self, bytes: &[u8]) -> Vec<u8> {
+        match self {
+            Encoding::Hex => bytes_to_hex(bytes.to_vec()).into_bytes(),
+            Encoding::Base64 => Base64::STANDARD.encode(bytes).into_bytes(),
+            Encoding::Base64Url => Base64::URL_SAFE.encode(bytes).into_bytes(),
+            Encoding::Base32 => base32_encode(bytes).into_bytes(),
+            Encoding::Ascii85 => ascii85_encode(bytes).into_bytes(),
+            Encoding::Decimal => format!("{:?}", bytes).into_bytes(),
+            Encoding::Raw => bytes.to_vec(),
+        }
+    }
+
+    // Text encodings forgive whitespace around the input, as files tend to end
+    // in a newline
+    pub fn decode(&self, input: &[u8]) -> Result<Vec<u8>, CryptoError> {
+        let (offset, text) = trim(input);
+        let shift = |e: CryptoError| match e {
+            CryptoError::InvalidCharacter { position, byte } => CryptoError::InvalidCharacter {
+                position: position + offset,
+                byte,
+            },
+            e => e,
+        };
+        match self {
+            Encoding::Hex => hex_to_bytes(text).map_err(shift),
+            Encoding::Base64 => Base64::STANDARD.decode(input),
+            Encoding::Base64Url => Base64::URL_SAFE.decode(input),
+            Encoding::Base32 => base32_decode(input),
+            Encoding::Ascii85 => ascii85_decode(input),
+            Encoding::Decimal => decimal_decode(input),
+            Encoding::Raw => Ok(input.to_vec()),
+        }
+    }
+}
+
+// (leading whitespace skipped, the rest without whitespace at either end)
+fn trim(input: &[u8]) -> (usize, &[u8]) {
+    let start = input
+        .iter()
+        .position(|c| !c.is_ascii_whitespace())
+        .unwrap_or(input.len());
+    let end = input
+        .iter()
+        .rposition(|c| !c.is_ascii_whitespace())
+        .map_or(start, |i| i + 1);
+    (start, &input[start..end])
+}
+
+const BASE32_CHARS: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+// RFC 4648 section 6, padded to a multiple of 8 characters
+pub fn base32_encode(bytes: &[u8]) -> String {
+    let mut out = String::new();
+    for chunk in bytes.chunks(5) {
+        let mut block = [0u8; 8];
+        block[3..3 + chunk.len()].copy_from_slice(chunk);
+        let bits = u64::from_be_bytes(block);
+
+        // n bytes need ceil(8n / 5) characters
+        let n = (chunk.len() * 8).div_ceil(5);
+        (0..8).for_each(|i| {
+            if i < n {
+                out.push(BASE32_CHARS[(bits >> (35 - 5 * i) & 0x1f) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        });
+    }
+    out
+}
+
+// leniently, like base64: whitespace anywhere and the padding optional
+pub fn base32_decode<T: AsRef<[u8]>>(s: T) -> Result<Vec<u8>, CryptoError> {
+    let input = s.as_ref();
+    let mut bytes = Vec::new();
+    let (mut bits, mut count) = (0u64, 0);
+    let (mut chars, mut padding) = (0, 0);
+    for (position, c) in input.iter().enumerate() {
+        if c.is_ascii_whitespace() {
+            continue;
+        }
+        if *c == b'=' {
+            padding += 1;
+            continue;
+        }
+        let Some(value) = BASE32_CHARS
+            .iter()
+            .position(|x| *x == c.to_ascii_uppercase())
+        else {
+            return Err(CryptoError::InvalidCharacter { position, byte: *c });
+        };
+        if padding > 0 {
+            return Err(CryptoError::InvalidPadding);
+        }
+
+        chars += 1;
+        bits = bits << 5 | value as u64;
+        count += 5;
+        if count >= 8 {
+            count -= 8;
+            bytes.push((bits >> count) as u8);
+        }
+    }
+
+    if ![0, 2, 4, 5, 7].contains(&(chars % 8)) {
+        return Err(CryptoError::BadLength(chars));
+    }
+    if padding != 0 && (chars + padding) % 8 != 0 {
+        return Err(CryptoError::InvalidPadding);
+    }
+    Ok(bytes)
+}
+
+// Adobe's ascii85 without the <~ ~> delimiters: each 4 bytes as 5 base 85 digits
+// from '!', with 'z' for 4 zero bytes, and a short last group of n bytes as n + 1
+// digits
+pub fn ascii85_encode(bytes: &[u8]) -> String {
+    let mut out = String::new();
+    for chunk in bytes.chunks(4) {
+        if chunk == [0; 4] {
+            out.push('z');
+            continue;
+        }
+        let mut block = [0u8; 4];
+        block[..chunk.len()].copy_from_slice(chunk);
+        let mut value = u32::from_be_bytes(block);
+
+        let mut digits = [0u8; 5];
+        for d in digits.iter_mut().rev() {
+            *d = (value % 85) as u8 + b'!';
+            value /= 85;
+        }
+        digits[..chunk.len() + 1]
+            .iter()
+            .for_each(|d| out.push(*d as char));
+    }
+    out
+}
+
+// with or without the delimiters, and whitespace anywhere
+pub fn ascii85_decode<T: AsRef<[u8]>>(s: T) -> Result<Vec<u8>, CryptoError> {
+    let (mut offset, mut text) = trim(s.as_ref());
+    if let Some(inner) = text.strip_prefix(b"<~") {
+        text = inner
+            .strip_suffix(b"~>")
+            .ok_or(CryptoError::MissingDelimiter {
+                position: offset + text.len(),
+                delimiter: "~>",
+            })?;
+        offset += 2;
+    }
+
+    let mut bytes = Vec::new();
+    let mut group: Vec<(usize, u8)> = Vec::with_capacity(5);
+    let flush = |group: &[(usize, u8)], bytes: &mut Vec<u8>| {
+        // a short group is made up with the highest digit, 'u', and cut back after
+        let value = (0..5).try_fold(0u32, |acc, i| {
+            let digit = group.get(i).map_or(84, |(_, d)| *d as u32);
+            acc.checked_mul(85).and_then(|acc| acc.checked_add(digit))
+        });
+        let value = value.ok_or(CryptoError::OutOfRange {
+            position: group[0].0,
+        })?;
+        bytes.extend(&value.to_be_bytes()[..group.len() - 1]);
+        Ok(())
+    };
+
+    for (i, c) in text.iter().enumerate() {
+        let position = offset + i;
+        match c {
+            c if c.is_ascii_whitespace() => continue,
+            b'z' if group.is_empty() => bytes.extend([0; 4]),
+            b'!'..=b'u' => {
+                group.push((position, c - b'!'));
+                if group.len() == 5 {
+                    flush(&group, &mut bytes)?;
+                    group.clear();
+                }
+            }
+            _ => return Err(CryptoError::InvalidCharacter { position, byte: *c }),
+        }
+    }
+    match group.len() {
+        0 => {}
+        1 => return Err(CryptoError::BadLength(1)),
+        _ => flush(&group, &mut bytes)?,
+    }
+    Ok(bytes)
+}
+
+pub fn decimal_decode<T: AsRef<[u8]>>(s: T) -> Result<Vec<u8>, CryptoError> {
+    let (mut offset, mut text) = trim(s.as_ref());
+    if let Some(inner) = text.strip_prefix(b"[") {
+        text = inner
+            .strip_suffix(b"]")
+            .ok_or(CryptoError::MissingDelimiter {
+                position: offset + text.len(),
+                delimiter: "]",
+            })?;
+        offset += 1;
+    }
+
+    let mut bytes = Vec::new();
+    let mut number: Option<(usize, u32)> = None;
+    for (i, c) in text.iter().chain([&b' ']).enumerate() {
+        let position = offset + i;
+        match c {
+            b'0'..=b'9' => {
+                let (start, value) = number.unwrap_or((position, 0));
+                let value = (value * 10 + (c - b'0') as u32).min(256);
+                number = Some((start, value));
+            }
+            b',' | b' ' | b'\t' | b'\r' | b'\n' => {
+                if let Some((start, value)) = number.take() {
+                    let byte = u8::try_from(value)
+                        .map_err(|_| CryptoError::OutOfRange { position: start })?;
+                    bytes.push(byte);
+                }
+            }
+            _ => return Err(CryptoError::InvalidCharacter { position, byte: *c }),
+        }
+    }
+    Ok(bytes)
+}
+
+// How much each encoding looks like the one the input is in, from what the
+// input is made of. Only encodings it decodes under are given, most likely first.
+pub fn autodetect(input: &[u8]) -> Vec<(Encoding, f64)> {
+    let (_, text) = trim(input);
+    let is_text = input
+        .iter()
+        .all(|c| c.is_ascii_graphic() || c.is_ascii_whitespace());
+    let has = |chars: &[u8]| text.iter().any(|c| chars.contains(c));
+    let has_lowercase = text.iter().any(u8::is_ascii_lowercase);
+
+    // Words decode as lowercase base32 or bare ascii85 often enough, but real output
+    // of either soon shows characters words rarely have: the digits 2 to 7, or
+    // symbols like # + ^. This is the chance of output as long as the input going
+    // without them, when each of the `size` characters is as likely as the next.
+    // It scales the 0.4 of unpadded base32, and words without spaces get half that,
+    // so they win once the chance is under even.
+    let length = text.iter().filter(|c| !c.is_ascii_whitespace()).count();
+    let chance_without = |tells: &[u8], size: usize| {
+        if has(tells) {
+            1.0
+        } else {
+            (1.0 - tells.len() as f64 / size as f64).powi(length as i32)
+        }
+    };
+
+    let mut candidates: Vec<(Encoding, f64)> = ENCODINGS
+        .into_iter()
+        .filter(|e| !text.is_empty() && e.decode(input).is_ok())
+        .map(|e| {
+            let confidence = match e {
+                // a small alphabet, and whole bytes
+                Encoding::Hex => 0.9,
+                Encoding::Decimal if has(b",[") => 0.95,
+                Encoding::Decimal if has(b" \t\r\n") => 0.7,
+                Encoding::Decimal => 0.3,
+                Encoding::Base32 if has_lowercase => 0.4 * chance_without(b"234567", 32),
+                Encoding::Base32 if text.len() % 8 == 0 => 0.8,
+                Encoding::Base32 => 0.4,
+                Encoding::Base64 if Base64::STANDARD.strict().decode(text).is_ok() => 0.75,
+                Encoding::Base64 => 0.5,
+                Encoding::Base64Url if has(b"-_") => 0.8,
+                Encoding::Base64Url => 0.35,
+                Encoding::Ascii85 if text.starts_with(b"<~") => 0.95,
+                Encoding::Ascii85 => 0.4 * chance_without(b"#$%&*+/<=>@[\\]^_`", 85),
+                Encoding::Raw if !is_text => 1.0,
+                // spaces between words, which none of the others have
+                Encoding::Raw if has(b" ") => 0.6,
+                Encoding::Raw => 0.4 * 0.5,
+            };
+            (e, confidence)
+        })
+        .collect();
+    if text.is_empty() {
+        candidates.push((Encoding::Raw, 1.0));
+    }
+    candidates.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+    candidates
+}
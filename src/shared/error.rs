@@ -0,0 +1,62 @@
+use std::fmt;
+
+// What can go wrong with input we did not produce ourselves
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CryptoError {
+    // the offending byte, and where in the input it was
+    InvalidCharacter {
+        position: usize,
+        byte: u8,
+    },
+    // the input opened with a delimiter, but ended at position without closing it
+    MissingDelimiter {
+        position: usize,
+        delimiter: &'static str,
+    },
+    // a number too big for what it has to fit in, starting at position
+    OutOfRange {
+        position: usize,
+    },
+    BadLength(usize),
+    // where two buffers have to be the same length: the first's, and the other's
+    LengthMismatch {
+        expected: usize,
+        actual: usize,
+    },
+    InvalidPadding,
+    // a message that parsed, but without a usable value for this field
+    MissingField(&'static str),
+    InvalidKeyLength(usize),
+    InvalidIvLength(usize),
+    OracleRejection,
+}
+
+impl fmt::Display for CryptoError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CryptoError::InvalidCharacter { position, byte } => write!(
+                f,
+                "invalid character {:?} at position {}",
+                *byte as char, position
+            ),
+            CryptoError::MissingDelimiter {
+                position,
+                delimiter,
+            } => write!(f, "missing closing {} at position {}", delimiter, position),
+            CryptoError::OutOfRange { position } => {
+                write!(f, "value out of range at position {}", position)
+            }
+            CryptoError::BadLength(length) => write!(f, "bad length {}", length),
+            CryptoError::LengthMismatch { expected, actual } => {
+                write!(f, "expected length {}, got {}", expected, actual)
+            }
+            CryptoError::InvalidPadding => write!(f, "invalid padding"),
+            CryptoError::MissingField(field) => write!(f, "missing or invalid {}", field),
+            CryptoError::InvalidKeyLength(length) => write!(f, "invalid key length {}", length),
+            CryptoError::InvalidIvLength(length) => write!(f, "invalid IV length {}", length),
+            CryptoError::OracleRejection => write!(f, "rejected by the oracle"),
+        }
+    }
+}
+
+impl std::error::Error for CryptoError {}
@@ -0,0 +1,169 @@
+use std::{
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+};
+
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+
+use super::{
+    dlog::{Group, ModP},
+    ec::{Curve, Point},
+};
+
+// A group whose operation can be made to go wrong, the way a carry lost out of
+// the low 64 bit word of a result would
+pub trait Faulty: Group {
+    fn corrupt(&self, x: &Self::Element) -> Self::Element;
+}
+
+fn lose_carry(x: &BigUint, p: &BigUint) -> BigUint {
+    let carry = (BigUint::from(1u32) << 64u32) % p;
+    (x + p - carry) % p
+}
+
+impl Faulty for ModP<'_> {
+    fn corrupt(&self, x: &BigUint) -> BigUint {
+        lose_carry(x, self.0)
+    }
+}
+
+// the x coordinate loses it, which leaves the point off the curve
+impl Faulty for Curve {
+    fn corrupt(&self, x: &Point) -> Point {
+        match x {
+            Point::Infinity => Point::Infinity,
+            Point::Affine(x, y) => Point::Affine(lose_carry(x, &self.p), y.clone()),
+        }
+    }
+}
+
+// Whether an operation faults is a function of its operands alone, and it does so
+// for about 1 in 2^bits of them. Anyone who knows the bug can tell in advance.
+#[derive(Debug, Clone, Copy)]
+pub struct FaultModel {
+    pub bits: u32,
+}
+
+impl FaultModel {
+    pub fn occurs<G: Group>(&self, group: &G, x: &G::Element, y: &G::Element) -> bool {
+        let mut hasher = DefaultHasher::new();
+        (group.fingerprint(x), group.fingerprint(y)).hash(&mut hasher);
+        hasher.finish().trailing_zeros() >= self.bits
+    }
+
+    pub fn op<G: Faulty>(&self, group: &G, x: &G::Element, y: &G::Element) -> G::Element {
+        let result = group.op(x, y);
+        if self.occurs(group, x, y) {
+            group.corrupt(&result)
+        } else {
+            result
+        }
+    }
+
+    // left to right square and multiply, from the top bit of k, on the faulty
+    // operation
+    pub fn pow<G: Faulty>(&self, group: &G, x: &G::Element, k: &BigUint) -> G::Element {
+        if k.is_zero() {
+            return group.pow(x, k);
+        }
+        (0..k.bits() - 1).rev().fold(x.clone(), |acc, i| {
+            let acc = self.op(group, &acc, &acc);
+            if k.bit(i) {
+                self.op(group, &acc, x)
+            } else {
+                acc
+            }
+        })
+    }
+}
+
+// Bob works out the shared secret with the faulty arithmetic, and the handshake
+// only goes through if he got the same answer as his peer, who did not
+pub fn get_handshake_oracle<G: Faulty>(
+    group: G,
+    fault: FaultModel,
+    secret: BigUint,
+) -> impl Fn(&G::Element) -> bool {
+    move |public: &G::Element| fault.pow(&group, public, &secret) == group.pow(public, &secret)
+}
+
+// The accumulator after the squaring for the next bit, when the bits after the
+// top one are `known`, or None if any operation up to there would fault
+fn simulate<G: Faulty>(
+    group: &G,
+    fault: FaultModel,
+    x: &G::Element,
+    known: &[bool],
+) -> Option<G::Element> {
+    let mut acc = x.clone();
+    for bit in known.iter().map(Some).chain([None]) {
+        if fault.occurs(group, &acc, &acc) {
+            return None;
+        }
+        acc = group.op(&acc, &acc);
+        if bit == Some(&true) {
+            if fault.occurs(group, &acc, x) {
+                return None;
+            }
+            acc = group.op(&acc, x);
+        }
+    }
+    Some(acc)
+}
+
+#[derive(Debug)]
+pub struct FaultResult {
+    pub secret: BigUint,
+    pub queries: usize,
+}
+
+// Recovers Bob's secret from the top bit down. For each bit, look for an x that
+// gets through the bits known so far cleanly, but would fault on the multiply if
+// the bit is set. If it is, the handshake fails for sure. If not, it only fails
+// when some later operation happens to fault, so one success settles it as 0,
+// and `confirmations` failures in a row are taken to mean 1. How many bits there
+// are is not known, so each prefix is checked against Bob's public key, up to
+// `max_bits` of them.
+pub fn fault_attack<G: Faulty>(
+    group: &G,
+    fault: FaultModel,
+    max_bits: u64,
+    confirmations: usize,
+    random: impl Fn() -> G::Element,
+    oracle: impl Fn(&G::Element) -> bool,
+    is_secret: impl Fn(&BigUint) -> bool,
+) -> Option<FaultResult> {
+    let mut secret = BigUint::one();
+    let mut known: Vec<bool> = Vec::new();
+    let mut queries = 0;
+
+    while !is_secret(&secret) {
+        if secret.bits() >= max_bits {
+            return None;
+        }
+
+        let mut failures = 0;
+        let bit = loop {
+            let x = random();
+            let Some(acc) = simulate(group, fault, &x, &known) else {
+                continue;
+            };
+            if !fault.occurs(group, &acc, &x) {
+                continue;
+            }
+
+            queries += 1;
+            if oracle(&x) {
+                break false;
+            }
+            failures += 1;
+            if failures == confirmations {
+                break true;
+            }
+        };
+        known.push(bit);
+        secret = (secret << 1u32) + u32::from(bit);
+    }
+    Some(FaultResult { secret, queries })
+}
@@ -0,0 +1,65 @@
+use std::collections::HashSet;
+
+use super::{
+    gcm::{ghash, ghash_blocks},
+    gf128::Gf128,
+    polynomial::{roots, Polynomial},
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct GcmMessage {
+    pub aad: Vec<u8>,
+    pub ciphertext: Vec<u8>,
+    pub tag: Vec<u8>,
+}
+
+// The tag as a polynomial in H: the n GHASH blocks are the coefficients of
+// H^n down to H, and the tag is the constant, so that it vanishes at H up to the
+// mask s = E_K(IV || 1).
+pub fn tag_polynomial(message: &GcmMessage) -> Polynomial {
+    let blocks = ghash_blocks(&message.aad, &message.ciphertext);
+    let coefficients = [Gf128::from_bytes(&message.tag)]
+        .into_iter()
+        .chain(blocks.into_iter().rev())
+        .collect();
+    Polynomial::new(coefficients)
+}
+
+// The "forbidden attack". Under the same key and nonce two tags share the mask,
+// which cancels in the sum of their polynomials and leaves H as one of its roots.
+// Every pair from the first message gives a set of candidates, and H is in all of
+// them, so a few messages usually narrow it down to H alone. It takes two to say
+// anything at all, so fewer gives no candidates.
+pub fn auth_key_candidates(messages: &[GcmMessage]) -> Vec<Gf128> {
+    if messages.len() < 2 {
+        return Vec::new();
+    }
+    let first = tag_polynomial(&messages[0]);
+    let candidate_sets: Vec<HashSet<Gf128>> = messages[1..]
+        .iter()
+        .map(|message| {
+            roots(&(&first + &tag_polynomial(message)))
+                .into_iter()
+                .collect()
+        })
+        .collect();
+
+    let mut candidates: Vec<Gf128> = candidate_sets
+        .first()
+        .map(|set| {
+            set.iter()
+                .filter(|h| candidate_sets.iter().all(|other| other.contains(h)))
+                .copied()
+                .collect()
+        })
+        .unwrap_or_default();
+    candidates.sort_by_key(|h| h.0);
+    candidates
+}
+
+// With H, the mask comes out of any message sent under the nonce, and then any
+// ciphertext can be given a valid tag under it.
+pub fn forge_tag(h: Gf128, known: &GcmMessage, aad: &[u8], ciphertext: &[u8]) -> Vec<u8> {
+    let mask = Gf128::from_bytes(&known.tag) + ghash(h, &known.aad, &known.ciphertext);
+    (ghash(h, aad, ciphertext) + mask).to_bytes().to_vec()
+}
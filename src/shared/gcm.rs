@@ -0,0 +1,114 @@
+use aes::{
+    cipher::{generic_array::GenericArray, BlockEncrypt},
+    Aes128,
+};
+
+use super::{aes::cipher, error::CryptoError, gf128::Gf128, xor::xor};
+
+fn encrypt_zero(cipher: &Aes128) -> Gf128 {
+    let mut block = GenericArray::from([0u8; 16]);
+    cipher.encrypt_block(&mut block);
+    Gf128::from_bytes(&block)
+}
+
+// H = E_K(0^128), the key GHASH runs under
+pub fn auth_key(key: &[u8]) -> Result<Gf128, CryptoError> {
+    Ok(encrypt_zero(&cipher(key)?))
+}
+
+// The 16 byte blocks GHASH works over: the associated data and the ciphertext,
+// each padded with zeros to a whole block, and then their lengths in bits.
+pub fn ghash_blocks(aad: &[u8], ciphertext: &[u8]) -> Vec<Gf128> {
+    let lengths = ((aad.len() as u128 * 8) << 64) | (ciphertext.len() as u128 * 8);
+    aad.chunks(16)
+        .chain(ciphertext.chunks(16))
+        .map(Gf128::from_bytes)
+        .chain([Gf128(lengths)])
+        .collect()
+}
+
+// Horner's rule, so a block b_i of n ends up multiplied by H^(n - i + 1)
+pub fn ghash(h: Gf128, aad: &[u8], ciphertext: &[u8]) -> Gf128 {
+    ghash_blocks(aad, ciphertext)
+        .into_iter()
+        .fold(Gf128::zero(), |acc, block| (acc + block) * h)
+}
+
+// only 96 bit IVs are supported
+fn check_iv(iv: &[u8]) -> Result<(), CryptoError> {
+    if iv.len() != 12 {
+        return Err(CryptoError::InvalidIvLength(iv.len()));
+    }
+    Ok(())
+}
+
+// the 96 bit IV followed by a 32 bit big endian block counter
+fn counter_block(iv: &[u8], counter: u32) -> GenericArray<u8, aes::cipher::consts::U16> {
+    GenericArray::clone_from_slice(&[iv, &counter.to_be_bytes()].concat())
+}
+
+// CTR from a counter of 2, as 1 is kept back to mask the tag
+fn ctr(cipher: &Aes128, iv: &[u8], bytes: &[u8]) -> Vec<u8> {
+    bytes
+        .chunks(16)
+        .enumerate()
+        .flat_map(|(i, chunk)| {
+            let mut keystream = counter_block(iv, i as u32 + 2);
+            cipher.encrypt_block(&mut keystream);
+            xor(chunk, &keystream[..chunk.len()])
+        })
+        .collect()
+}
+
+fn tag(cipher: &Aes128, iv: &[u8], aad: &[u8], ciphertext: &[u8]) -> Vec<u8> {
+    let mut mask = counter_block(iv, 1);
+    cipher.encrypt_block(&mut mask);
+    let s = ghash(encrypt_zero(cipher), aad, ciphertext) + Gf128::from_bytes(&mask);
+    s.to_bytes().to_vec()
+}
+
+pub fn compute_tag(
+    key: &[u8],
+    iv: &[u8],
+    aad: &[u8],
+    ciphertext: &[u8],
+) -> Result<Vec<u8>, CryptoError> {
+    let cipher = cipher(key)?;
+    check_iv(iv)?;
+    Ok(tag(&cipher, iv, aad, ciphertext))
+}
+
+// (ciphertext, tag)
+pub fn encrypt_gcm(
+    key: &[u8],
+    iv: &[u8],
+    aad: &[u8],
+    plaintext: &[u8],
+) -> Result<(Vec<u8>, Vec<u8>), CryptoError> {
+    let cipher = cipher(key)?;
+    check_iv(iv)?;
+    let ciphertext = ctr(&cipher, iv, plaintext);
+    let tag = tag(&cipher, iv, aad, &ciphertext);
+    Ok((ciphertext, tag))
+}
+
+pub fn decrypt_gcm(
+    key: &[u8],
+    iv: &[u8],
+    aad: &[u8],
+    ciphertext: &[u8],
+    tag: &[u8],
+) -> Result<Vec<u8>, CryptoError> {
+    let cipher = cipher(key)?;
+    check_iv(iv)?;
+    let expected = self::tag(&cipher, iv, aad, ciphertext);
+    // compare every byte, rather than stopping at the first difference
+    let difference = expected
+        .iter()
+        .zip(tag)
+        .fold(0u8, |acc, (a, b)| acc | (a ^ b));
+    if tag.len() != expected.len() || difference != 0 {
+        return Err(CryptoError::OracleRejection);
+    }
+    Ok(ctr(&cipher, iv, ciphertext))
+}
@@ -0,0 +1,88 @@
+use std::ops::{Add, Mul};
+
+use rand::Rng;
+
+// An element of GF(2^128) = GF(2)[x] / (x^128 + x^7 + x^2 + x + 1), the way GCM
+// reads a block: the first bit of the first byte is the coefficient of x^0, so
+// the block as a big endian u128 has the polynomial's coefficients reflected.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Gf128(pub u128);
+
+// x^7 + x^2 + x + 1, reflected, which is what reducing x^128 leaves behind
+const R: u128 = 0xe1 << 120;
+
+impl Gf128 {
+    pub fn zero() -> Gf128 {
+        Gf128(0)
+    }
+
+    pub fn one() -> Gf128 {
+        Gf128(1 << 127)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.0 == 0
+    }
+
+    // up to 16 bytes, short blocks padded with zeros
+    pub fn from_bytes(bytes: &[u8]) -> Gf128 {
+        assert!(bytes.len() <= 16);
+        let mut block = [0u8; 16];
+        block[..bytes.len()].copy_from_slice(bytes);
+        Gf128(u128::from_be_bytes(block))
+    }
+
+    pub fn to_bytes(&self) -> [u8; 16] {
+        self.0.to_be_bytes()
+    }
+
+    pub fn random() -> Gf128 {
+        Gf128(rand::thread_rng().gen())
+    }
+
+    pub fn pow(&self, mut e: u128) -> Gf128 {
+        let mut base = *self;
+        let mut result = Gf128::one();
+        while e > 0 {
+            if e & 1 == 1 {
+                result = result * base;
+            }
+            base = base * base;
+            e >>= 1;
+        }
+        result
+    }
+
+    // a^(2^128 - 2), as the multiplicative group has order 2^128 - 1
+    pub fn inverse(&self) -> Option<Gf128> {
+        (!self.is_zero()).then(|| self.pow(u128::MAX - 1))
+    }
+}
+
+// characteristic 2, so addition and subtraction are both xor
+impl Add for Gf128 {
+    type Output = Gf128;
+
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn add(self, other: Gf128) -> Gf128 {
+        Gf128(self.0 ^ other.0)
+    }
+}
+
+// Shift and add, algorithm 1 of the GCM specification. Multiplying by x is a
+// right shift in the reflected order, with R folded back in for what falls off.
+impl Mul for Gf128 {
+    type Output = Gf128;
+
+    fn mul(self, other: Gf128) -> Gf128 {
+        let mut z = 0u128;
+        let mut v = other.0;
+        for i in (0..128).rev() {
+            if (self.0 >> i) & 1 == 1 {
+                z ^= v;
+            }
+            v = if v & 1 == 1 { (v >> 1) ^ R } else { v >> 1 };
+        }
+        Gf128(z)
+    }
+}
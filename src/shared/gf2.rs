@@ -0,0 +1,238 @@
+use std::ops::Mul;
+
+use rand::Rng;
+
+use super::gf128::Gf128;
+
+// A vector over GF(2), packed 64 bits to a word with bit i in word i / 64
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Gf2Vector {
+    pub len: usize,
+    pub words: Vec<u64>,
+}
+
+impl Gf2Vector {
+    pub fn zeros(len: usize) -> Gf2Vector {
+        Gf2Vector {
+            len,
+            words: vec![0; len.div_ceil(64)],
+        }
+    }
+
+    pub fn unit(len: usize, i: usize) -> Gf2Vector {
+        let mut v = Gf2Vector::zeros(len);
+        v.set(i, true);
+        v
+    }
+
+    pub fn random(len: usize) -> Gf2Vector {
+        let mut rng = rand::thread_rng();
+        let mut v = Gf2Vector::zeros(len);
+        (0..len).for_each(|i| v.set(i, rng.gen()));
+        v
+    }
+
+    pub fn get(&self, i: usize) -> bool {
+        (self.words[i / 64] >> (i % 64)) & 1 == 1
+    }
+
+    pub fn set(&mut self, i: usize, bit: bool) {
+        if bit {
+            self.words[i / 64] |= 1 << (i % 64);
+        } else {
+            self.words[i / 64] &= !(1 << (i % 64));
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.words.iter().all(|w| *w == 0)
+    }
+
+    // addition, which is xor
+    pub fn add_assign(&mut self, other: &Gf2Vector) {
+        assert_eq!(self.len, other.len);
+        self.words
+            .iter_mut()
+            .zip(&other.words)
+            .for_each(|(a, b)| *a ^= b);
+    }
+
+    pub fn dot(&self, other: &Gf2Vector) -> bool {
+        assert_eq!(self.len, other.len);
+        self.words
+            .iter()
+            .zip(&other.words)
+            .fold(0, |acc, (a, b)| acc ^ (a & b).count_ones())
+            & 1
+            == 1
+    }
+
+    // bit i is the coefficient of x^i
+    pub fn from_gf128(a: Gf128) -> Gf2Vector {
+        let mut v = Gf2Vector::zeros(128);
+        (0..128).for_each(|i| v.set(i, (a.0 >> (127 - i)) & 1 == 1));
+        v
+    }
+
+    pub fn to_gf128(&self) -> Gf128 {
+        assert_eq!(self.len, 128);
+        Gf128((0..128).fold(0, |acc, i| acc | (self.get(i) as u128) << (127 - i)))
+    }
+}
+
+// A matrix over GF(2) as its rows
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Gf2Matrix {
+    pub cols: usize,
+    pub rows: Vec<Gf2Vector>,
+}
+
+impl Gf2Matrix {
+    pub fn zeros(rows: usize, cols: usize) -> Gf2Matrix {
+        Gf2Matrix {
+            cols,
+            rows: vec![Gf2Vector::zeros(cols); rows],
+        }
+    }
+
+    pub fn identity(n: usize) -> Gf2Matrix {
+        Gf2Matrix {
+            cols: n,
+            rows: (0..n).map(|i| Gf2Vector::unit(n, i)).collect(),
+        }
+    }
+
+    pub fn from_rows(cols: usize, rows: Vec<Gf2Vector>) -> Gf2Matrix {
+        assert!(rows.iter().all(|row| row.len == cols));
+        Gf2Matrix { cols, rows }
+    }
+
+    pub fn from_columns(rows: usize, columns: &[Gf2Vector]) -> Gf2Matrix {
+        Gf2Matrix::from_rows(rows, columns.to_vec()).transpose()
+    }
+
+    pub fn row_count(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn get(&self, r: usize, c: usize) -> bool {
+        self.rows[r].get(c)
+    }
+
+    pub fn set(&mut self, r: usize, c: usize, bit: bool) {
+        self.rows[r].set(c, bit)
+    }
+
+    pub fn column(&self, c: usize) -> Gf2Vector {
+        let mut v = Gf2Vector::zeros(self.row_count());
+        (0..self.row_count()).for_each(|r| v.set(r, self.get(r, c)));
+        v
+    }
+
+    pub fn transpose(&self) -> Gf2Matrix {
+        Gf2Matrix {
+            cols: self.row_count(),
+            rows: (0..self.cols).map(|c| self.column(c)).collect(),
+        }
+    }
+
+    pub fn mul_vector(&self, v: &Gf2Vector) -> Gf2Vector {
+        let mut result = Gf2Vector::zeros(self.row_count());
+        for (r, row) in self.rows.iter().enumerate() {
+            result.set(r, row.dot(v));
+        }
+        result
+    }
+
+    // Gaussian elimination to reduced row echelon form, along with the column of
+    // each row's pivot. Rows past the pivots are all zero.
+    pub fn reduce(&self) -> (Gf2Matrix, Vec<usize>) {
+        let mut m = self.clone();
+        let mut pivots = Vec::new();
+        for c in 0..m.cols {
+            let r = pivots.len();
+            let Some(p) = (r..m.row_count()).find(|i| m.get(*i, c)) else {
+                continue;
+            };
+            m.rows.swap(r, p);
+            let pivot = m.rows[r].clone();
+            for (i, row) in m.rows.iter_mut().enumerate() {
+                if i != r && row.get(c) {
+                    row.add_assign(&pivot);
+                }
+            }
+            pivots.push(c);
+            if pivots.len() == m.row_count() {
+                break;
+            }
+        }
+        (m, pivots)
+    }
+
+    pub fn rank(&self) -> usize {
+        self.reduce().1.len()
+    }
+
+    // A basis of {v : Mv = 0}: one vector for each column without a pivot, set in
+    // that column and cancelling it out in the pivot columns.
+    pub fn kernel(&self) -> Vec<Gf2Vector> {
+        let (m, pivots) = self.reduce();
+        let mut is_pivot = vec![false; self.cols];
+        pivots.iter().for_each(|c| is_pivot[*c] = true);
+
+        (0..self.cols)
+            .filter(|c| !is_pivot[*c])
+            .map(|free| {
+                let mut v = Gf2Vector::unit(self.cols, free);
+                for (r, c) in pivots.iter().enumerate() {
+                    v.set(*c, m.get(r, free));
+                }
+                v
+            })
+            .collect()
+    }
+}
+
+impl Mul for &Gf2Matrix {
+    type Output = Gf2Matrix;
+
+    // each row of the product is the sum of the rows of other that the row of self picks
+    fn mul(self, other: &Gf2Matrix) -> Gf2Matrix {
+        assert_eq!(self.cols, other.row_count());
+        let rows = self
+            .rows
+            .iter()
+            .map(|row| {
+                let mut sum = Gf2Vector::zeros(other.cols);
+                (0..self.cols)
+                    .filter(|k| row.get(*k))
+                    .for_each(|k| sum.add_assign(&other.rows[k]));
+                sum
+            })
+            .collect();
+        Gf2Matrix {
+            cols: other.cols,
+            rows,
+        }
+    }
+}
+
+// The 128x128 matrix of y -> c * y, whose column j is c * x^j
+pub fn multiplication_matrix(c: Gf128) -> Gf2Matrix {
+    let columns: Vec<Gf2Vector> = (0..128)
+        .map(|j| Gf2Vector::from_gf128(c * Gf128(1 << (127 - j))))
+        .collect();
+    Gf2Matrix::from_columns(128, &columns)
+}
+
+// Squaring is linear in characteristic 2, (a + b)^2 = a^2 + b^2, so it has a
+// matrix too, whose column j is x^2j
+pub fn squaring_matrix() -> Gf2Matrix {
+    let columns: Vec<Gf2Vector> = (0..128)
+        .map(|j| {
+            let x = Gf128(1 << (127 - j));
+            Gf2Vector::from_gf128(x * x)
+        })
+        .collect();
+    Gf2Matrix::from_columns(128, &columns)
+}
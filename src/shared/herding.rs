@@ -0,0 +1,192 @@
+use std::{collections::HashMap, fs, io, path::Path};
+
+use rand::Rng;
+
+use super::{
+    error::CryptoError,
+    md::{md_padding, MdHash, BLOCK_SIZE},
+    second_preimage::find_cross_collision,
+};
+
+// A binary tree of 2^k states funnelling into a single root. Level 0 is the leaves,
+// and `blocks[i][j]` takes node j of level i to node j / 2 of level i + 1.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diamond {
+    pub hash: MdHash,
+    pub states: Vec<Vec<u64>>,
+    pub blocks: Vec<Vec<[u8; BLOCK_SIZE]>>,
+}
+
+impl Diamond {
+    // Costs about 2^(k + b/2 + 1) compressions, so is worth keeping around.
+    pub fn build(hash: &MdHash, k: usize) -> Diamond {
+        let mut rng = rand::thread_rng();
+        let mut leaves = HashMap::new();
+        while leaves.len() < 1 << k {
+            let state: u64 = rng.gen::<u64>() & (u64::MAX >> (64 - hash.bits));
+            leaves.insert(state, ());
+        }
+
+        let mut states = vec![leaves.into_keys().collect::<Vec<u64>>()];
+        let mut blocks = Vec::new();
+        for _ in 0..k {
+            let (level_blocks, parents): (Vec<[[u8; BLOCK_SIZE]; 2]>, Vec<u64>) = states
+                .last()
+                .unwrap()
+                .chunks(2)
+                .map(|pair| {
+                    let (a, b, parent, _) = find_cross_collision(hash, pair[0], pair[1]);
+                    ([a.try_into().unwrap(), b.try_into().unwrap()], parent)
+                })
+                .unzip();
+
+            blocks.push(level_blocks.into_iter().flatten().collect());
+            states.push(parents);
+        }
+
+        Diamond {
+            hash: *hash,
+            states,
+            blocks,
+        }
+    }
+
+    pub fn k(&self) -> usize {
+        self.blocks.len()
+    }
+
+    fn total_len(&self, prefix_blocks: usize) -> usize {
+        (prefix_blocks + 1 + self.k()) * BLOCK_SIZE
+    }
+
+    // The hash to publish up front, for any prefix of `prefix_blocks` blocks. The
+    // length padding has to be known now, so the length of the prefix must be too.
+    pub fn prediction(&self, prefix_blocks: usize) -> u64 {
+        let root = self.states[self.k()][0];
+        let padding = md_padding(&[], self.total_len(prefix_blocks));
+        self.hash.compress_all(root, &padding)
+    }
+
+    // Pads `prefix` with spaces to the `prefix_blocks` blocks the prediction was made
+    // for, then finds a linking block into one of the leaves and follows the tree to
+    // the root. Returns the message and how many compressions it took to find the
+    // link, about 2^(b - k).
+    pub fn link(
+        &self,
+        prefix: &[u8],
+        prefix_blocks: usize,
+    ) -> Result<(Vec<u8>, usize), CryptoError> {
+        if prefix.len() > prefix_blocks * BLOCK_SIZE {
+            return Err(CryptoError::BadLength(prefix.len()));
+        }
+        let mut message = prefix.to_vec();
+        message.resize(prefix_blocks * BLOCK_SIZE, b' ');
+        let state = self.hash.compress_all(self.hash.iv, &message);
+
+        let leaves: HashMap<u64, usize> = self.states[0]
+            .iter()
+            .enumerate()
+            .map(|(i, s)| (*s, i))
+            .collect();
+
+        let mut rng = rand::thread_rng();
+        let mut calls = 0;
+        let (link, mut node) = loop {
+            let block: [u8; BLOCK_SIZE] = rng.gen();
+            calls += 1;
+            if let Some(i) = leaves.get(&self.hash.compress(state, &block)) {
+                break (block, *i);
+            }
+        };
+
+        message.extend(link);
+        for level in &self.blocks {
+            message.extend(level[node]);
+            node /= 2;
+        }
+        Ok((message, calls))
+    }
+
+    // bits, iv, k, then each level's states followed by its blocks, all little endian
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend(self.hash.bits.to_le_bytes());
+        bytes.extend(self.hash.iv.to_le_bytes());
+        bytes.extend((self.k() as u32).to_le_bytes());
+        for (i, states) in self.states.iter().enumerate() {
+            states.iter().for_each(|s| bytes.extend(s.to_le_bytes()));
+            if let Some(blocks) = self.blocks.get(i) {
+                blocks.iter().for_each(|b| bytes.extend(b));
+            }
+        }
+        bytes
+    }
+
+    // Anything short or long comes back as BadLength, and a value that can't be
+    // right, such as a state wider than the hash, as OutOfRange where it starts
+    pub fn from_bytes(bytes: &[u8]) -> Result<Diamond, CryptoError> {
+        let mut offset = 0;
+        let bits = u32::from_le_bytes(take(bytes, &mut offset, 4)?.try_into().unwrap());
+        if bits == 0 || bits > 64 {
+            return Err(CryptoError::OutOfRange { position: 0 });
+        }
+        let state = |offset: &mut usize| {
+            let position = *offset;
+            let state = u64::from_le_bytes(take(bytes, offset, 8)?.try_into().unwrap());
+            if state > u64::MAX >> (64 - bits) {
+                return Err(CryptoError::OutOfRange { position });
+            }
+            Ok(state)
+        };
+
+        let iv = state(&mut offset)?;
+        let position = offset;
+        let k = u32::from_le_bytes(take(bytes, &mut offset, 4)?.try_into().unwrap()) as usize;
+        if k >= 32 {
+            return Err(CryptoError::OutOfRange { position });
+        }
+
+        let mut states = Vec::new();
+        let mut blocks = Vec::new();
+        for i in 0..=k {
+            let level = (0..1usize << (k - i))
+                .map(|_| state(&mut offset))
+                .collect::<Result<Vec<u64>, CryptoError>>()?;
+            states.push(level);
+
+            if i < k {
+                let level = (0..1usize << (k - i))
+                    .map(|_| Ok(take(bytes, &mut offset, BLOCK_SIZE)?.try_into().unwrap()))
+                    .collect::<Result<Vec<[u8; BLOCK_SIZE]>, CryptoError>>()?;
+                blocks.push(level);
+            }
+        }
+        if offset != bytes.len() {
+            return Err(CryptoError::BadLength(bytes.len()));
+        }
+
+        Ok(Diamond {
+            hash: MdHash { bits, iv },
+            states,
+            blocks,
+        })
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        fs::write(path, self.to_bytes())
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Diamond> {
+        let bytes = fs::read(path)?;
+        Diamond::from_bytes(&bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+// the next n bytes from offset on, moving offset past them
+fn take<'a>(bytes: &'a [u8], offset: &mut usize, n: usize) -> Result<&'a [u8], CryptoError> {
+    if bytes.len() - *offset < n {
+        return Err(CryptoError::BadLength(bytes.len()));
+    }
+    *offset += n;
+    Ok(&bytes[*offset - n..*offset])
+}
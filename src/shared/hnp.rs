@@ -0,0 +1,96 @@
+use num_bigint::{BigInt, BigUint, RandBigInt};
+use num_integer::Integer;
+use num_rational::BigRational;
+use num_traits::{One, Signed, Zero};
+
+use super::{
+    bignum::invmod,
+    ec::{EcParams, Point},
+    ecdsa::{hash_message, sign_with_nonce, Signature},
+    lattice::{lll, Vector},
+};
+
+// Bob's signer draws nonces with the low `bits` bits always zero
+pub fn get_biased_signing_oracle(
+    params: EcParams,
+    secret: BigUint,
+    bits: u64,
+) -> impl Fn(&[u8]) -> Signature {
+    move |message: &[u8]| {
+        let mut rng = rand::thread_rng();
+        let bound = &params.q >> bits;
+        loop {
+            let k = rng.gen_biguint_range(&BigUint::one(), &bound) << bits;
+            if let Some(signature) = sign_with_nonce(&params, &secret, message, &k) {
+                break signature;
+            }
+        }
+    }
+}
+
+fn rational(n: &BigUint) -> BigRational {
+    BigRational::from_integer(n.clone().into())
+}
+
+// Each signature has k = 2^l * b with b < q / 2^l, and k = (H(m) + d * r) / s, so
+// d * t - u = b mod q for t = r / (s * 2^l) and u = H(m) / (-s * 2^l). That is the
+// hidden number problem, and d * (t_1..t_n) - (u_1..u_n) is unusually close to the
+// lattice of multiples of q. Embedded in the rows
+//
+//     q    0   ...  0    0    0
+//     0    q   ...  0    0    0
+//     ...
+//     t_1  t_2 ... t_n  ct   0
+//     u_1  u_2 ... u_n  0    cu
+//
+// with ct = 1 / 2^l and cu = q / 2^l, it becomes the short vector
+// (b_1, ..., b_n, d * ct, -cu), which LLL should turn up with cu in the last place.
+pub fn hnp_attack(
+    params: &EcParams,
+    public: &Point,
+    signatures: &[(Vec<u8>, Signature)],
+    bits: u64,
+) -> Option<BigUint> {
+    let q = &params.q;
+    let n = signatures.len();
+    let scale = BigUint::one() << bits;
+
+    let mut basis: Vec<Vector> = (0..n)
+        .map(|i| {
+            let mut row = vec![BigRational::zero(); n + 2];
+            row[i] = rational(q);
+            row
+        })
+        .collect();
+
+    let mut t_row = vec![BigRational::zero(); n + 2];
+    let mut u_row = vec![BigRational::zero(); n + 2];
+    for (i, (message, Signature { r, s })) in signatures.iter().enumerate() {
+        let inverse = invmod(&(s * &scale % q), q)?;
+        t_row[i] = rational(&(r * &inverse % q));
+        u_row[i] = rational(&((q - hash_message(message, q) * &inverse % q) % q));
+    }
+    let cu = rational(q) / rational(&scale);
+    t_row[n] = rational(&scale).recip();
+    u_row[n + 1] = cu.clone();
+    basis.push(t_row);
+    basis.push(u_row);
+
+    let delta = BigRational::new(BigInt::from(99), BigInt::from(100));
+    lll(&basis, &delta).into_iter().find_map(|row| {
+        if row[n + 1].abs() != cu {
+            return None;
+        }
+        // d * ct, or its negation along with the whole row
+        let d = &row[n] * rational(&scale);
+        let d = if row[n + 1] == cu { -d } else { d };
+        if !d.is_integer() {
+            return None;
+        }
+        let d = d
+            .to_integer()
+            .mod_floor(&BigInt::from(q.clone()))
+            .to_biguint()?;
+        (params.curve.scalar_mul(&params.g, &d) == *public).then_some(d)
+    })
+}
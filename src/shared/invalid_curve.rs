@@ -0,0 +1,89 @@
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+
+use super::{
+    bignum::{crt, small_factors},
+    ec::{point_to_bytes, Curve, EcParams, Point},
+    mac::hmac_sha256,
+    subgroup::SubgroupResult,
+};
+
+// A point of order r on a curve of the given order, for a prime r dividing it.
+// The group need not be cyclic, so (order / r) * P can be the identity for every P;
+// instead take P into the part of order a power of r and multiply by r until the
+// next step would reach the identity.
+pub fn point_of_order(curve: &Curve, order: &BigUint, r: u64) -> Point {
+    let mut cofactor = order.clone();
+    while (&cofactor % r).is_zero() {
+        cofactor /= r;
+    }
+
+    loop {
+        let mut h = curve.scalar_mul(&curve.random_point(), &cofactor);
+        if h == Point::Infinity {
+            continue;
+        }
+        loop {
+            let next = curve.scalar_mul(&h, &BigUint::from(r));
+            if next == Point::Infinity {
+                return h;
+            }
+            h = next;
+        }
+    }
+}
+
+// the multiples of h are the only keys Bob can end up with, so his tag gives away
+// his secret mod r
+pub fn recover_residue(
+    curve: &Curve,
+    h: &Point,
+    r: u64,
+    oracle: impl Fn(&Point) -> (Vec<u8>, Vec<u8>),
+) -> BigUint {
+    let (message, tag) = oracle(h);
+
+    let mut k = Point::Infinity;
+    for x in 0..r {
+        if hmac_sha256(&point_to_bytes(&k), &message) == tag {
+            return BigUint::from(x);
+        }
+        k = curve.add(&k, h);
+    }
+    unreachable!("h has order r, so one of its multiples is the key")
+}
+
+// Pohlig-Hellman again, but the small subgroups come from other curves. Bob's
+// arithmetic never involves b, so a point from y^2 = x^3 + ax + b' is multiplied
+// on that curve instead of his own. Each distinct small factor of their orders
+// gives the secret mod that factor, until there is enough to cover q.
+pub fn invalid_curve_attack(
+    params: &EcParams,
+    curves: &[(Curve, BigUint)],
+    bound: u64,
+    oracle: impl Fn(&Point) -> (Vec<u8>, Vec<u8>),
+) -> SubgroupResult {
+    let mut residues: Vec<(BigUint, BigUint)> = Vec::new();
+    let mut modulus = BigUint::one();
+    for (curve, order) in curves {
+        for r in small_factors(order, bound) {
+            if modulus > params.q {
+                break;
+            }
+            if residues.iter().any(|(_, m)| m == &BigUint::from(r)) {
+                continue;
+            }
+
+            let h = point_of_order(curve, order, r);
+            residues.push((recover_residue(curve, &h, r, &oracle), BigUint::from(r)));
+            modulus *= r;
+        }
+    }
+
+    let (residue, modulus) = crt(&residues);
+    SubgroupResult {
+        residue,
+        modulus,
+        queries: residues.len(),
+    }
+}
@@ -0,0 +1,111 @@
+use std::collections::HashMap;
+
+use rand::Rng;
+
+use super::md::{md_padding, MdHash, BLOCK_SIZE};
+
+// Birthday search for two different blocks taking `state` to the same state.
+// Returns the blocks, the new state and how many compressions it took.
+pub fn find_block_collision(hash: &MdHash, state: u64) -> ([Vec<u8>; 2], u64, usize) {
+    let mut rng = rand::thread_rng();
+    let mut seen: HashMap<u64, Vec<u8>> = HashMap::new();
+    let mut calls = 0;
+
+    loop {
+        let block: [u8; BLOCK_SIZE] = rng.gen();
+        let next = hash.compress(state, &block);
+        calls += 1;
+
+        match seen.get(&next) {
+            Some(other) if other.as_slice() != block => {
+                break ([other.clone(), block.to_vec()], next, calls);
+            }
+            _ => {
+                seen.insert(next, block.to_vec());
+            }
+        }
+    }
+}
+
+// `n` colliding block pairs chained from one state. Picking either block of each
+// pair gives 2^n messages of n blocks that all reach `state`, for only n times
+// the work of a single collision.
+#[derive(Debug, Clone)]
+pub struct Multicollision {
+    pub pairs: Vec<[Vec<u8>; 2]>,
+    pub state: u64,
+    pub calls: usize,
+}
+
+impl Multicollision {
+    pub fn messages(&self) -> impl Iterator<Item = Vec<u8>> + '_ {
+        (0..1u64 << self.pairs.len()).map(move |choice| {
+            self.pairs
+                .iter()
+                .enumerate()
+                .flat_map(|(i, pair)| pair[((choice >> i) & 1) as usize].clone())
+                .collect()
+        })
+    }
+}
+
+pub fn multicollision(hash: &MdHash, state: u64, n: usize) -> Multicollision {
+    let mut collision = Multicollision {
+        pairs: Vec::new(),
+        state,
+        calls: 0,
+    };
+    for _ in 0..n {
+        extend_multicollision(hash, &mut collision);
+    }
+    collision
+}
+
+// doubles the number of colliding messages
+pub fn extend_multicollision(hash: &MdHash, collision: &mut Multicollision) {
+    let (pair, next, calls) = find_block_collision(hash, collision.state);
+    collision.pairs.push(pair);
+    collision.state = next;
+    collision.calls += calls;
+}
+
+#[derive(Debug)]
+pub struct CascadeCollision {
+    pub messages: [Vec<u8>; 2],
+    // colliding messages generated in `f` before two of them also collided in `g`
+    pub multicollision_size: usize,
+    pub f_calls: usize,
+    pub g_calls: usize,
+}
+
+// Finds a collision in `f(m) || g(m)`, which is no stronger than its stronger half.
+// Joux multicollisions in the cheap `f` give 2^n messages that agree in `f`, and
+// once 2^n is around the birthday bound of `g` two of them will likely collide
+// in `g` as well. If not, add another block pair and look again.
+pub fn cascade_collision(f: &MdHash, g: &MdHash) -> CascadeCollision {
+    let mut collision = multicollision(f, f.iv, (g.bits / 2) as usize);
+    let mut g_calls = 0;
+
+    loop {
+        // every message has the same length, so they all end on the same padding block
+        let len = collision.pairs.len() * BLOCK_SIZE;
+        let padding = &md_padding(&[], len)[..];
+
+        let mut seen: HashMap<u64, Vec<u8>> = HashMap::new();
+        for message in collision.messages() {
+            let digest = g.compress_all(g.compress_all(g.iv, &message), padding);
+            g_calls += collision.pairs.len() + 1;
+
+            if let Some(other) = seen.insert(digest, message.clone()) {
+                return CascadeCollision {
+                    messages: [other, message],
+                    multicollision_size: 1 << collision.pairs.len(),
+                    f_calls: collision.calls,
+                    g_calls,
+                };
+            }
+        }
+
+        extend_multicollision(f, &mut collision);
+    }
+}
@@ -0,0 +1,106 @@
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{One, Signed, Zero};
+
+pub type Vector = Vec<BigRational>;
+
+pub fn dot(a: &[BigRational], b: &[BigRational]) -> BigRational {
+    assert_eq!(a.len(), b.len());
+    a.iter()
+        .zip(b)
+        .fold(BigRational::zero(), |acc, (x, y)| acc + x * y)
+}
+
+// a + k * b
+fn add_scaled(a: &[BigRational], b: &[BigRational], k: &BigRational) -> Vector {
+    a.iter().zip(b).map(|(x, y)| x + k * y).collect()
+}
+
+// integer rows as rationals
+pub fn from_integers(rows: &[Vec<i64>]) -> Vec<Vector> {
+    rows.iter()
+        .map(|row| {
+            row.iter()
+                .map(|x| BigRational::from_integer(BigInt::from(*x)))
+                .collect()
+        })
+        .collect()
+}
+
+// The orthogonalised basis b*, along with mu[i][j] = <b_i, b*_j> / <b*_j, b*_j>
+// for j < i, so that b_i = b*_i + sum(mu[i][j] * b*_j).
+pub fn gram_schmidt_with_mu(basis: &[Vector]) -> (Vec<Vector>, Vec<Vec<BigRational>>) {
+    let n = basis.len();
+    let mut orthogonal: Vec<Vector> = Vec::with_capacity(n);
+    let mut mu = vec![vec![BigRational::zero(); n]; n];
+
+    for i in 0..n {
+        let mut v = basis[i].clone();
+        for j in 0..i {
+            let norm = dot(&orthogonal[j], &orthogonal[j]);
+            if norm.is_zero() {
+                continue;
+            }
+            mu[i][j] = dot(&basis[i], &orthogonal[j]) / norm;
+            v = add_scaled(&v, &orthogonal[j], &-&mu[i][j]);
+        }
+        orthogonal.push(v);
+    }
+    (orthogonal, mu)
+}
+
+pub fn gram_schmidt(basis: &[Vector]) -> Vec<Vector> {
+    gram_schmidt_with_mu(basis).0
+}
+
+// Lenstra-Lenstra-Lovasz reduction of a basis of linearly independent rows, with
+// the Lovasz condition parameter delta, usually 3/4 or a little under 1. Rather
+// than redo the Gram-Schmidt after every change, mu and the squared lengths of
+// b* are kept up to date as rows are reduced and swapped (Cohen, algorithm 2.6.3).
+pub fn lll(basis: &[Vector], delta: &BigRational) -> Vec<Vector> {
+    let n = basis.len();
+    let mut b = basis.to_vec();
+    let (orthogonal, mut mu) = gram_schmidt_with_mu(&b);
+    let mut norms: Vec<BigRational> = orthogonal.iter().map(|v| dot(v, v)).collect();
+    let half = BigRational::new(BigInt::one(), BigInt::from(2));
+
+    let mut k = 1;
+    while k < n {
+        // size reduction, so |mu[k][j]| <= 1/2 for every j < k
+        for j in (0..k).rev() {
+            if mu[k][j].abs() > half {
+                let r = mu[k][j].round();
+                b[k] = add_scaled(&b[k], &b[j], &-&r);
+                let (upper, lower) = mu.split_at_mut(k);
+                for (x, y) in lower[0].iter_mut().zip(&upper[j][..j]) {
+                    *x -= &r * y;
+                }
+                mu[k][j] -= &r;
+            }
+        }
+
+        // the Lovasz condition, otherwise swap b[k - 1] and b[k] and step back
+        let mu_k = mu[k][k - 1].clone();
+        if norms[k] >= (delta - &mu_k * &mu_k) * &norms[k - 1] {
+            k += 1;
+            continue;
+        }
+
+        b.swap(k, k - 1);
+        let norm = &norms[k] + &mu_k * &mu_k * &norms[k - 1];
+        mu[k][k - 1] = &mu_k * &norms[k - 1] / &norm;
+        norms[k] = &norms[k - 1] * &norms[k] / &norm;
+        norms[k - 1] = norm;
+        for j in 0..k - 1 {
+            let (upper, lower) = mu.split_at_mut(k);
+            std::mem::swap(&mut upper[k - 1][j], &mut lower[0][j]);
+        }
+        for i in k + 1..n {
+            let t = mu[i][k].clone();
+            mu[i][k] = &mu[i][k - 1] - &mu_k * &t;
+            mu[i][k - 1] = t + &mu[k][k - 1] * &mu[i][k];
+        }
+        k = (k - 1).max(1);
+    }
+    b
+}
@@ -0,0 +1,62 @@
+use super::{
+    aes::{decrypt_ecb, encrypt_cbc, pkcs7_padding},
+    error::CryptoError,
+    sha256::{self, sha256},
+    xor::{xor, xor_with_key},
+};
+
+// the last block of the CBC encryption of the padded message
+pub fn cbc_mac(key: &[u8], message: &[u8], iv: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    let ct = encrypt_cbc(key, pkcs7_padding(message, 16), iv)?;
+    Ok(ct[ct.len() - 16..].to_vec())
+}
+
+// CBC-MAC used as a hash function, the key and IV are public
+const HASH_KEY: &[u8] = b"YELLOW SUBMARINE";
+const HASH_IV: [u8; 16] = [0; 16];
+
+pub fn cbc_mac_hash(message: &[u8]) -> Vec<u8> {
+    cbc_mac(HASH_KEY, message, &HASH_IV).expect("the hash key and IV are 16 bytes")
+}
+
+// Builds `pad(prefix) || glue || suffix` hashing to `target_mac`. Knowing the key we
+// can run the suffix backwards from the target to find the CBC state it needs to
+// start from, and a single glue block takes the prefix's state to that one.
+pub fn cbc_mac_collide(
+    target_mac: &[u8],
+    prefix: &[u8],
+    suffix: &[u8],
+) -> Result<Vec<u8>, CryptoError> {
+    let blocks = pkcs7_padding(suffix, 16);
+    let needed_state = blocks
+        .chunks(16)
+        .rev()
+        .try_fold(target_mac.to_vec(), |state, block| {
+            Ok::<_, CryptoError>(xor(&decrypt_ecb(HASH_KEY, state)?, block))
+        })?;
+
+    let padded_prefix = pkcs7_padding(prefix, 16);
+    let prefix_state = cbc_mac_hash(prefix);
+    let glue = xor(&decrypt_ecb(HASH_KEY, needed_state)?, &prefix_state);
+
+    Ok([padded_prefix, glue, suffix.to_vec()].concat())
+}
+
+// RFC 2104, for any hash with the given block size
+pub fn hmac(hash: fn(&[u8]) -> Vec<u8>, block_size: usize, key: &[u8], message: &[u8]) -> Vec<u8> {
+    let mut key = if key.len() > block_size {
+        hash(key)
+    } else {
+        key.to_vec()
+    };
+    key.resize(block_size, 0);
+
+    let inner_key = xor_with_key(&key, 0x36);
+    let outer_key = xor_with_key(&key, 0x5c);
+    let inner = hash(&[inner_key.as_slice(), message].concat());
+    hash(&[outer_key, inner].concat())
+}
+
+pub fn hmac_sha256(key: &[u8], message: &[u8]) -> Vec<u8> {
+    hmac(sha256, sha256::BLOCK_SIZE, key, message)
+}
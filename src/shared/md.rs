@@ -0,0 +1,68 @@
+use aes::{
+    cipher::{generic_array::GenericArray, BlockEncrypt, KeyInit},
+    Aes128,
+};
+
+pub const BLOCK_SIZE: usize = 16;
+
+// A deliberately weak Merkle-Damgård hash with a `bits` wide state, so that
+// collisions are cheap enough to find by brute force. The compression function
+// is Davies-Meyer over AES-128, with the message block as the key:
+//
+//     H' = E(m, H) ^ H
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MdHash {
+    pub bits: u32,
+    pub iv: u64,
+}
+
+impl MdHash {
+    pub fn new(bits: u32) -> MdHash {
+        assert!(bits > 0 && bits <= 64);
+        MdHash {
+            bits,
+            iv: 0x0123_4567_89ab_cdef & Self::mask(bits),
+        }
+    }
+
+    fn mask(bits: u32) -> u64 {
+        u64::MAX >> (64 - bits)
+    }
+
+    pub fn compress(&self, state: u64, block: &[u8]) -> u64 {
+        let cipher = Aes128::new(GenericArray::from_slice(block));
+
+        let mut b = [0u8; BLOCK_SIZE];
+        b[..8].copy_from_slice(&state.to_le_bytes());
+        let mut b = GenericArray::from(b);
+        cipher.encrypt_block(&mut b);
+
+        let encrypted = u64::from_le_bytes(b[..8].try_into().unwrap());
+        (encrypted ^ state) & Self::mask(self.bits)
+    }
+
+    // runs the compression function over whole blocks, without any padding
+    pub fn compress_all(&self, state: u64, blocks: &[u8]) -> u64 {
+        assert_eq!(blocks.len() % BLOCK_SIZE, 0);
+        blocks
+            .chunks(BLOCK_SIZE)
+            .fold(state, |state, block| self.compress(state, block))
+    }
+
+    pub fn hash(&self, message: &[u8]) -> u64 {
+        self.compress_all(self.iv, &md_padding(message, message.len()))
+    }
+}
+
+// 0x80, zeros, then the 64 bit big endian bit length of the whole message. the
+// length is separate so that padding can be built for a message that was
+// hashed in pieces.
+pub fn md_padding(message: &[u8], total_len: usize) -> Vec<u8> {
+    let mut padded = message.to_vec();
+    padded.push(0x80);
+    while padded.len() % BLOCK_SIZE != BLOCK_SIZE - 8 {
+        padded.push(0);
+    }
+    padded.extend(((total_len * 8) as u64).to_be_bytes());
+    padded
+}
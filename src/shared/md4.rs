@@ -0,0 +1,124 @@
+pub const IV: [u32; 4] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476];
+
+pub const ROUND_2_CONSTANT: u32 = 0x5a827999;
+pub const ROUND_3_CONSTANT: u32 = 0x6ed9eba1;
+
+// shift amounts for each of the four steps of a round
+pub const ROUND_1_SHIFTS: [u32; 4] = [3, 7, 11, 19];
+pub const ROUND_2_SHIFTS: [u32; 4] = [3, 5, 9, 13];
+pub const ROUND_3_SHIFTS: [u32; 4] = [3, 9, 11, 15];
+
+// which message word each step of rounds 2 and 3 reads
+pub const ROUND_2_WORDS: [usize; 16] = [0, 4, 8, 12, 1, 5, 9, 13, 2, 6, 10, 14, 3, 7, 11, 15];
+pub const ROUND_3_WORDS: [usize; 16] = [0, 8, 4, 12, 2, 10, 6, 14, 1, 9, 5, 13, 3, 11, 7, 15];
+
+pub fn f(x: u32, y: u32, z: u32) -> u32 {
+    (x & y) | (!x & z)
+}
+
+pub fn g(x: u32, y: u32, z: u32) -> u32 {
+    (x & y) | (x & z) | (y & z)
+}
+
+pub fn h(x: u32, y: u32, z: u32) -> u32 {
+    x ^ y ^ z
+}
+
+pub fn round_1(a: u32, b: u32, c: u32, d: u32, m: u32, s: u32) -> u32 {
+    a.wrapping_add(f(b, c, d)).wrapping_add(m).rotate_left(s)
+}
+
+pub fn round_2(a: u32, b: u32, c: u32, d: u32, m: u32, s: u32) -> u32 {
+    a.wrapping_add(g(b, c, d))
+        .wrapping_add(m)
+        .wrapping_add(ROUND_2_CONSTANT)
+        .rotate_left(s)
+}
+
+pub fn round_3(a: u32, b: u32, c: u32, d: u32, m: u32, s: u32) -> u32 {
+    a.wrapping_add(h(b, c, d))
+        .wrapping_add(m)
+        .wrapping_add(ROUND_3_CONSTANT)
+        .rotate_left(s)
+}
+
+pub fn block_to_words(block: &[u8]) -> [u32; 16] {
+    assert_eq!(block.len(), 64);
+    let mut words = [0; 16];
+    block
+        .chunks(4)
+        .enumerate()
+        .for_each(|(i, w)| words[i] = u32::from_le_bytes(w.try_into().unwrap()));
+    words
+}
+
+pub fn words_to_block(words: &[u32; 16]) -> Vec<u8> {
+    words.iter().flat_map(|w| w.to_le_bytes()).collect()
+}
+
+// Every intermediate value of the compression function, in the order they are
+// computed: a1, d1, c1, b1, a2, ... b12. The initial state is not included.
+pub fn md4_steps(state: [u32; 4], words: &[u32; 16]) -> Vec<u32> {
+    // a, d, c, b order, so the value being replaced is always four back
+    let mut values = vec![state[0], state[3], state[2], state[1]];
+
+    for step in 0..48 {
+        let i = values.len();
+        let (a, b, c, d) = (values[i - 4], values[i - 1], values[i - 2], values[i - 3]);
+        let next = match step / 16 {
+            0 => round_1(a, b, c, d, words[step], ROUND_1_SHIFTS[step % 4]),
+            1 => round_2(
+                a,
+                b,
+                c,
+                d,
+                words[ROUND_2_WORDS[step % 16]],
+                ROUND_2_SHIFTS[step % 4],
+            ),
+            _ => round_3(
+                a,
+                b,
+                c,
+                d,
+                words[ROUND_3_WORDS[step % 16]],
+                ROUND_3_SHIFTS[step % 4],
+            ),
+        };
+        values.push(next);
+    }
+
+    values.split_off(4)
+}
+
+pub fn md4_compress(state: [u32; 4], words: &[u32; 16]) -> [u32; 4] {
+    let steps = md4_steps(state, words);
+    let (a, d, c, b) = (steps[44], steps[45], steps[46], steps[47]);
+    [
+        state[0].wrapping_add(a),
+        state[1].wrapping_add(b),
+        state[2].wrapping_add(c),
+        state[3].wrapping_add(d),
+    ]
+}
+
+// like MD5, the length is little endian
+pub fn md4_padding(message: &[u8], total_len: usize) -> Vec<u8> {
+    let mut padded = message.to_vec();
+    padded.push(0x80);
+    while padded.len() % 64 != 56 {
+        padded.push(0);
+    }
+    padded.extend(((total_len * 8) as u64).to_le_bytes());
+    padded
+}
+
+pub fn md4(message: &[u8]) -> Vec<u8> {
+    md4_padding(message, message.len())
+        .chunks(64)
+        .fold(IV, |state, block| {
+            md4_compress(state, &block_to_words(block))
+        })
+        .iter()
+        .flat_map(|w| w.to_le_bytes())
+        .collect()
+}
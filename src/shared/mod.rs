@@ -1,6 +1,40 @@
 pub mod aes;
 pub mod analysis;
+pub mod bank;
+pub mod bignum;
+pub mod bleichenbacher;
 pub mod conversion;
+pub mod crime;
+pub mod dh;
+pub mod dlog;
+pub mod dsks;
+pub mod ec;
+pub mod ecdsa;
+pub mod encoding;
+pub mod error;
+pub mod fault;
+pub mod forbidden;
+pub mod gcm;
+pub mod gf128;
+pub mod gf2;
 pub mod hamming;
+pub mod herding;
+pub mod hnp;
+pub mod invalid_curve;
+pub mod joux;
 pub mod kv;
+pub mod lattice;
+pub mod mac;
+pub mod md;
+pub mod md4;
+pub mod montgomery;
+pub mod polynomial;
+pub mod rc4;
+pub mod rsa;
+pub mod second_preimage;
+pub mod sha256;
+pub mod subgroup;
+pub mod truncated_mac;
+pub mod twist;
+pub mod wang;
 pub mod xor;
@@ -0,0 +1,138 @@
+use num_bigint::BigUint;
+use num_traits::{One, Zero};
+
+use super::{
+    bignum::invmod,
+    ec::{Curve, Point},
+    mac::hmac_sha256,
+};
+
+// Bv^2 = u^3 + Au^2 + u over GF(p)
+#[derive(Debug, Clone, PartialEq)]
+pub struct MontgomeryCurve {
+    pub a: BigUint,
+    pub b: BigUint,
+    pub p: BigUint,
+}
+
+impl MontgomeryCurve {
+    // v^2 = u^3 + 534u^2 + u, the cryptopals curve from challenge 59 in disguise
+    pub fn cryptopals() -> MontgomeryCurve {
+        MontgomeryCurve {
+            a: BigUint::from(534u32),
+            b: BigUint::one(),
+            p: BigUint::parse_bytes(b"233970423115425145524320034830162017933", 10).unwrap(),
+        }
+    }
+
+    fn sub(&self, x: &BigUint, y: &BigUint) -> BigUint {
+        (x + &self.p - y % &self.p) % &self.p
+    }
+
+    fn inverse(&self, x: &BigUint) -> BigUint {
+        invmod(x, &self.p).unwrap()
+    }
+
+    // u^3 + Au^2 + u, which has to be B times a square for u to be on the curve
+    pub fn rhs(&self, u: &BigUint) -> BigUint {
+        let u2 = u * u % &self.p;
+        (&u2 * u + &self.a * &u2 + u) % &self.p
+    }
+
+    // A / 3B, the shift between u and the Weierstrass x
+    fn shift(&self) -> BigUint {
+        &self.a * self.inverse(&(3u32 * &self.b)) % &self.p
+    }
+
+    // the short Weierstrass form, with a = (3 - A^2) / 3B^2 and
+    // b = (2A^3 - 9A) / 27B^3
+    pub fn weierstrass(&self) -> Curve {
+        let p = &self.p;
+        let a2 = &self.a * &self.a % p;
+        let b2 = &self.b * &self.b % p;
+        let a = self.sub(&BigUint::from(3u32), &a2) * self.inverse(&(3u32 * b2 % p));
+        let b = self.sub(&(2u32 * &a2 * &self.a), &(9u32 * &self.a))
+            * self.inverse(&(27u32 * self.b.modpow(&BigUint::from(3u32), p)));
+        Curve::new(&(a % p), &(b % p), p)
+    }
+
+    // (u + A/3) / B
+    pub fn to_weierstrass_x(&self, u: &BigUint) -> BigUint {
+        (u + self.shift()) * self.inverse(&self.b) % &self.p
+    }
+
+    // (u, v) => ((u + A/3) / B, v / B)
+    pub fn to_weierstrass(&self, point: &Point) -> Point {
+        match point {
+            Point::Infinity => Point::Infinity,
+            Point::Affine(u, v) => Point::Affine(
+                self.to_weierstrass_x(u),
+                v * self.inverse(&self.b) % &self.p,
+            ),
+        }
+    }
+
+    // (x, y) => (Bx - A/3, By)
+    pub fn from_weierstrass(&self, point: &Point) -> Point {
+        match point {
+            Point::Infinity => Point::Infinity,
+            Point::Affine(x, y) => Point::Affine(
+                self.sub(&(x * &self.b), &self.shift()),
+                y * &self.b % &self.p,
+            ),
+        }
+    }
+
+    // u(k * P) from u(P) alone. The point is never checked, so u from the twist
+    // is multiplied on the twist. The identity comes back as 0.
+    pub fn ladder(&self, u: &BigUint, k: &BigUint) -> BigUint {
+        let p = &self.p;
+        let (mut u2, mut w2) = (BigUint::one(), BigUint::zero());
+        let (mut u3, mut w3) = (u.clone(), BigUint::one());
+
+        for i in (0..p.bits()).rev() {
+            let bit = k.bit(i);
+            if bit {
+                std::mem::swap(&mut u2, &mut u3);
+                std::mem::swap(&mut w2, &mut w3);
+            }
+
+            let next_u3 = self.sub(&(&u2 * &u3 % p), &(&w2 * &w3 % p)).pow(2) % p;
+            let next_w3 = u * self.sub(&(&u2 * &w3 % p), &(&w2 * &u3 % p)).pow(2) % p;
+            let uu = &u2 * &u2 % p;
+            let ww = &w2 * &w2 % p;
+            let uw = &u2 * &w2 % p;
+            let next_u2 = self.sub(&uu, &ww).pow(2) % p;
+            let next_w2 = 4u32 * &uw * ((&uu + &self.a * &uw + &ww) % p) % p;
+            (u2, w2, u3, w3) = (next_u2, next_w2, next_u3, next_w3);
+
+            if bit {
+                std::mem::swap(&mut u2, &mut u3);
+                std::mem::swap(&mut w2, &mut w3);
+            }
+        }
+        u2 * w2.modpow(&(p - 2u32), p) % p
+    }
+
+    // u(P + Q) from u(P), u(Q) and u(P - Q), for P != Q
+    pub fn differential_add(&self, u_p: &BigUint, u_q: &BigUint, u_diff: &BigUint) -> BigUint {
+        let p = &self.p;
+        let numerator = self.sub(&(u_p * u_q), &BigUint::one()).pow(2) % p;
+        let denominator = u_diff * self.sub(u_p, u_q).pow(2) % p;
+        numerator * self.inverse(&denominator) % p
+    }
+}
+
+// Bob takes any u at all, and replies with his message and its MAC under u(xP)
+pub fn get_ladder_mac_oracle(
+    curve: MontgomeryCurve,
+    secret: BigUint,
+    message: &[u8],
+) -> impl Fn(&BigUint) -> (Vec<u8>, Vec<u8>) {
+    let message = message.to_vec();
+    move |u: &BigUint| {
+        let k = curve.ladder(u, &secret);
+        let tag = hmac_sha256(&k.to_bytes_be(), &message);
+        (message.clone(), tag)
+    }
+}
@@ -0,0 +1,270 @@
+use std::ops::{Add, Mul};
+
+use num_bigint::BigUint;
+
+use super::gf128::Gf128;
+
+// A polynomial over GF(2^128), lowest degree first with no trailing zeros, so the
+// zero polynomial has no coefficients at all.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Polynomial {
+    pub coefficients: Vec<Gf128>,
+}
+
+impl Polynomial {
+    pub fn new(mut coefficients: Vec<Gf128>) -> Polynomial {
+        while coefficients.last().is_some_and(Gf128::is_zero) {
+            coefficients.pop();
+        }
+        Polynomial { coefficients }
+    }
+
+    pub fn zero() -> Polynomial {
+        Polynomial::new(Vec::new())
+    }
+
+    pub fn constant(c: Gf128) -> Polynomial {
+        Polynomial::new(vec![c])
+    }
+
+    pub fn one() -> Polynomial {
+        Polynomial::constant(Gf128::one())
+    }
+
+    pub fn x() -> Polynomial {
+        Polynomial::new(vec![Gf128::zero(), Gf128::one()])
+    }
+
+    pub fn random(degree: usize) -> Polynomial {
+        Polynomial::new((0..=degree).map(|_| Gf128::random()).collect())
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.coefficients.is_empty()
+    }
+
+    pub fn is_one(&self) -> bool {
+        *self == Polynomial::one()
+    }
+
+    // None for the zero polynomial
+    pub fn degree(&self) -> Option<usize> {
+        self.coefficients.len().checked_sub(1)
+    }
+
+    pub fn leading(&self) -> Gf128 {
+        self.coefficients.last().copied().unwrap_or_default()
+    }
+
+    pub fn scale(&self, c: Gf128) -> Polynomial {
+        Polynomial::new(self.coefficients.iter().map(|a| *a * c).collect())
+    }
+
+    // scaled to a leading coefficient of 1, the zero polynomial stays as it is
+    pub fn monic(&self) -> Polynomial {
+        match self.leading().inverse() {
+            Some(inverse) => self.scale(inverse),
+            None => self.clone(),
+        }
+    }
+
+    pub fn evaluate(&self, x: Gf128) -> Gf128 {
+        self.coefficients
+            .iter()
+            .rev()
+            .fold(Gf128::zero(), |acc, c| acc * x + *c)
+    }
+
+    // (quotient, remainder), long division
+    pub fn divmod(&self, divisor: &Polynomial) -> (Polynomial, Polynomial) {
+        let d = divisor.degree().expect("division by the zero polynomial");
+        let inverse = divisor.leading().inverse().unwrap();
+
+        let mut remainder = self.coefficients.clone();
+        let mut quotient = vec![Gf128::zero(); remainder.len().saturating_sub(d)];
+        for i in (d..remainder.len()).rev() {
+            let c = remainder[i] * inverse;
+            quotient[i - d] = c;
+            for (j, b) in divisor.coefficients.iter().enumerate() {
+                remainder[i - d + j] = remainder[i - d + j] + c * *b;
+            }
+        }
+        remainder.truncate(d);
+        (Polynomial::new(quotient), Polynomial::new(remainder))
+    }
+
+    pub fn rem(&self, modulus: &Polynomial) -> Polynomial {
+        self.divmod(modulus).1
+    }
+
+    // monic, with gcd(0, 0) = 0
+    pub fn gcd(&self, other: &Polynomial) -> Polynomial {
+        let (mut a, mut b) = (self.clone(), other.clone());
+        while !b.is_zero() {
+            (a, b) = (b.clone(), a.rem(&b));
+        }
+        a.monic()
+    }
+
+    pub fn modpow(&self, e: &BigUint, modulus: &Polynomial) -> Polynomial {
+        let base = self.rem(modulus);
+        (0..e.bits())
+            .rev()
+            .fold(Polynomial::one().rem(modulus), |acc, i| {
+                let acc = (&acc * &acc).rem(modulus);
+                if e.bit(i) {
+                    (&acc * &base).rem(modulus)
+                } else {
+                    acc
+                }
+            })
+    }
+
+    // self^(2^128) mod modulus, by repeated squaring
+    fn frobenius(&self, modulus: &Polynomial) -> Polynomial {
+        (0..128).fold(self.rem(modulus), |acc, _| (&acc * &acc).rem(modulus))
+    }
+
+    // the formal derivative: i * c_i is c_i for odd i and vanishes for even i
+    pub fn derivative(&self) -> Polynomial {
+        Polynomial::new(
+            self.coefficients
+                .iter()
+                .enumerate()
+                .skip(1)
+                .map(|(i, c)| if i % 2 == 1 { *c } else { Gf128::zero() })
+                .collect(),
+        )
+    }
+
+    // the g with g^2 = self, for a polynomial with only even powers. Squaring is
+    // linear here, so each coefficient only needs its own square root, a^(2^127).
+    pub fn sqrt(&self) -> Polynomial {
+        Polynomial::new(
+            self.coefficients
+                .iter()
+                .step_by(2)
+                .map(|c| c.pow(1 << 127))
+                .collect(),
+        )
+    }
+}
+
+impl Add for &Polynomial {
+    type Output = Polynomial;
+
+    fn add(self, other: &Polynomial) -> Polynomial {
+        let n = self.coefficients.len().max(other.coefficients.len());
+        let get = |p: &Polynomial, i: usize| p.coefficients.get(i).copied().unwrap_or_default();
+        Polynomial::new((0..n).map(|i| get(self, i) + get(other, i)).collect())
+    }
+}
+
+impl Mul for &Polynomial {
+    type Output = Polynomial;
+
+    fn mul(self, other: &Polynomial) -> Polynomial {
+        if self.is_zero() || other.is_zero() {
+            return Polynomial::zero();
+        }
+        let mut product =
+            vec![Gf128::zero(); self.coefficients.len() + other.coefficients.len() - 1];
+        for (i, a) in self.coefficients.iter().enumerate() {
+            for (j, b) in other.coefficients.iter().enumerate() {
+                product[i + j] = product[i + j] + *a * *b;
+            }
+        }
+        Polynomial::new(product)
+    }
+}
+
+// The square-free factors of a monic f, with their multiplicities. In
+// characteristic 2 whatever is left once the derivative has nothing more to say
+// is a perfect square, which gets its square root factored in turn.
+pub fn square_free_factorization(f: &Polynomial) -> Vec<(Polynomial, usize)> {
+    let mut factors = Vec::new();
+    let mut c = f.gcd(&f.derivative());
+    let mut w = f.divmod(&c).0;
+
+    let mut i = 1;
+    while !w.is_one() {
+        let y = w.gcd(&c);
+        let factor = w.divmod(&y).0;
+        if !factor.is_one() {
+            factors.push((factor, i));
+        }
+        c = c.divmod(&y).0;
+        w = y;
+        i += 1;
+    }
+
+    if !c.is_one() {
+        factors.extend(
+            square_free_factorization(&c.sqrt())
+                .into_iter()
+                .map(|(g, m)| (g, 2 * m)),
+        );
+    }
+    factors
+}
+
+// Splits a square-free monic f into products of its irreducible factors of each
+// degree d, as gcd(f, x^(q^d) - x) collects every one of degree d, q = 2^128.
+pub fn distinct_degree_factorization(f: &Polynomial) -> Vec<(Polynomial, usize)> {
+    let mut factors = Vec::new();
+    let mut rest = f.clone();
+    let mut h = Polynomial::x().rem(f);
+
+    let mut d = 1;
+    while rest.degree().unwrap_or(0) >= 2 * d {
+        h = h.frobenius(&rest);
+        let g = rest.gcd(&(&h + &Polynomial::x()));
+        if !g.is_one() {
+            rest = rest.divmod(&g).0;
+            h = h.rem(&rest);
+            factors.push((g, d));
+        }
+        d += 1;
+    }
+    if let Some(degree) = rest.degree().filter(|degree| *degree > 0) {
+        factors.push((rest, degree));
+    }
+    factors
+}
+
+// Cantor-Zassenhaus for a monic f that is a product of irreducibles of degree d.
+// In characteristic 2 the split comes from the trace, a + a^2 + a^4 + ... up to
+// a^(2^(128d - 1)), which is 0 or 1 mod each factor for a random a, and which
+// it is differs from one factor to the next about half the time.
+pub fn equal_degree_factorization(f: &Polynomial, d: usize) -> Vec<Polynomial> {
+    let n = f.degree().unwrap_or(0);
+    if n <= d {
+        return vec![f.clone()];
+    }
+
+    loop {
+        let a = Polynomial::random(n - 1).rem(f);
+        let (trace, _) = (0..128 * d).fold((Polynomial::zero(), a), |(trace, power), _| {
+            let next = (&power * &power).rem(f);
+            (&trace + &power, next)
+        });
+
+        let g = f.gcd(&trace);
+        if !g.is_one() && g != *f {
+            let mut factors = equal_degree_factorization(&g, d);
+            factors.extend(equal_degree_factorization(&f.divmod(&g).0, d));
+            break factors;
+        }
+    }
+}
+
+// The distinct roots of f in GF(2^128), its monic linear factors x + r
+pub fn roots(f: &Polynomial) -> Vec<Gf128> {
+    square_free_factorization(&f.monic())
+        .into_iter()
+        .flat_map(|(g, _)| distinct_degree_factorization(&g))
+        .filter(|(_, d)| *d == 1)
+        .flat_map(|(g, _)| equal_degree_factorization(&g, 1))
+        .map(|linear| linear.coefficients[0])
+        .collect()
+}
@@ -0,0 +1,165 @@
+use std::thread;
+
+use rand::Rng;
+
+use super::conversion::base64_to_bytes;
+
+#[derive(Debug, Clone)]
+pub struct Rc4 {
+    s: [u8; 256],
+    i: u8,
+    j: u8,
+}
+
+impl Rc4 {
+    pub fn new(key: &[u8]) -> Rc4 {
+        assert!(!key.is_empty() && key.len() <= 256);
+
+        let mut s = [0u8; 256];
+        s.iter_mut().enumerate().for_each(|(i, x)| *x = i as u8);
+
+        let mut j: u8 = 0;
+        for i in 0..256 {
+            j = j.wrapping_add(s[i]).wrapping_add(key[i % key.len()]);
+            s.swap(i, j as usize);
+        }
+
+        Rc4 { s, i: 0, j: 0 }
+    }
+
+    pub fn next_byte(&mut self) -> u8 {
+        self.i = self.i.wrapping_add(1);
+        self.j = self.j.wrapping_add(self.s[self.i as usize]);
+        self.s.swap(self.i as usize, self.j as usize);
+
+        let k = self.s[self.i as usize].wrapping_add(self.s[self.j as usize]);
+        self.s[k as usize]
+    }
+
+    pub fn keystream(&mut self, len: usize) -> Vec<u8> {
+        (0..len).map(|_| self.next_byte()).collect()
+    }
+
+    pub fn apply(&mut self, bytes: &[u8]) -> Vec<u8> {
+        bytes.iter().map(|b| b ^ self.next_byte()).collect()
+    }
+}
+
+pub fn encrypt_rc4(key: &[u8], bytes: &[u8]) -> Vec<u8> {
+    Rc4::new(key).apply(bytes)
+}
+
+pub fn decrypt_rc4(key: &[u8], bytes: &[u8]) -> Vec<u8> {
+    encrypt_rc4(key, bytes)
+}
+
+// encrypts request || cookie under a fresh random 128 bit key every time
+pub fn get_rc4_oracle(cookie: &[u8]) -> impl Fn(&[u8]) -> Vec<u8> + Sync {
+    let cookie = cookie.to_vec();
+    move |request: &[u8]| {
+        let key: [u8; 16] = rand::thread_rng().gen();
+        encrypt_rc4(&key, &[request, &cookie].concat())
+    }
+}
+
+pub fn get_cookie_oracle() -> impl Fn(&[u8]) -> Vec<u8> + Sync {
+    let cookie = base64_to_bytes("QkUgU1VSRSBUTyBEUklOSyBZT1VSIE9WQUxUSU5F").unwrap();
+    get_rc4_oracle(&cookie)
+}
+
+// Runs `sample` `samples` times split over `threads` threads, counting the value of
+// each byte it returns by position.
+pub fn collect_statistics(
+    sample: impl Fn() -> Vec<u8> + Sync,
+    len: usize,
+    samples: usize,
+    threads: usize,
+) -> Vec<[u64; 256]> {
+    let sample = &sample;
+    thread::scope(|scope| {
+        let workers = (0..threads)
+            .map(|t| {
+                // spread the remainder over the first few threads
+                let share = samples / threads + usize::from(t < samples % threads);
+                scope.spawn(move || {
+                    let mut counts = vec![[0u64; 256]; len];
+                    for _ in 0..share {
+                        sample()
+                            .iter()
+                            .take(len)
+                            .enumerate()
+                            .for_each(|(i, b)| counts[i][*b as usize] += 1);
+                    }
+                    counts
+                })
+            })
+            .collect::<Vec<_>>();
+
+        workers
+            .into_iter()
+            .map(|worker| worker.join().unwrap())
+            .fold(vec![[0u64; 256]; len], |mut total, counts| {
+                total.iter_mut().zip(counts).for_each(|(t, c)| {
+                    t.iter_mut().zip(c).for_each(|(t, c)| *t += c);
+                });
+                total
+            })
+    })
+}
+
+// the first `len` keystream bytes under random keys
+pub fn keystream_statistics(len: usize, samples: usize, threads: usize) -> Vec<[u64; 256]> {
+    collect_statistics(
+        || {
+            let key: [u8; 16] = rand::thread_rng().gen();
+            Rc4::new(&key).keystream(len)
+        },
+        len,
+        samples,
+        threads,
+    )
+}
+
+// Z16 (index 15) leans towards 240 and Z32 (index 31) towards 224
+const BIASES: [(usize, u8); 2] = [(15, 240), (31, 224)];
+
+// Recovers up to 32 bytes of a cookie appended to our request. Padding the request
+// with 0..16 bytes slides each cookie byte under the biased keystream bytes, and
+// each padding length gives a byte for both of them. The bias is slight, so it
+// takes in the region of 2^24 `samples` per padding to get every byte right.
+pub fn recover_rc4_cookie(
+    oracle: impl Fn(&[u8]) -> Vec<u8> + Sync,
+    cookie_len: usize,
+    samples: usize,
+    threads: usize,
+) -> Vec<u8> {
+    assert!(cookie_len <= 32);
+    let mut cookie = vec![0; cookie_len];
+
+    for padding in 0..16 {
+        // which cookie bytes this padding puts under the biased keystream bytes
+        let targets = BIASES
+            .iter()
+            .filter_map(|(position, bias)| {
+                let i = position.checked_sub(padding).filter(|i| *i < cookie_len)?;
+                Some((*position, *bias, i))
+            })
+            .collect::<Vec<(usize, u8, usize)>>();
+        if targets.is_empty() {
+            continue;
+        }
+
+        let request = vec![b'A'; padding];
+        let counts = collect_statistics(|| oracle(&request), 32, samples, threads);
+
+        for (position, bias, i) in targets {
+            let (most_common, _) = counts[position]
+                .iter()
+                .enumerate()
+                .max_by_key(|(_, count)| **count)
+                .unwrap();
+            cookie[i] = most_common as u8 ^ bias;
+        }
+    }
+    cookie
+}
@@ -0,0 +1,170 @@
+use num_bigint::{BigInt, BigUint};
+use num_integer::Integer;
+use num_rational::BigRational;
+use num_traits::{One, Zero};
+use rand::Rng;
+
+use super::{
+    bignum::{gen_prime, invmod},
+    sha256::sha256,
+};
+
+#[derive(Debug, Clone)]
+pub struct RsaPublicKey {
+    pub e: BigUint,
+    pub n: BigUint,
+}
+
+#[derive(Debug, Clone)]
+pub struct RsaPrivateKey {
+    pub d: BigUint,
+    pub n: BigUint,
+}
+
+pub fn generate_keypair(bits: u64, e: u32) -> (RsaPublicKey, RsaPrivateKey) {
+    let e = BigUint::from(e);
+    loop {
+        let p = gen_prime(bits / 2);
+        let q = gen_prime(bits - bits / 2);
+        if p == q {
+            continue;
+        }
+
+        // e has to be invertible mod the totient, otherwise try new primes
+        let et = (&p - 1u32) * (&q - 1u32);
+        if let Some(d) = invmod(&e, &et) {
+            let n = p * q;
+            break (
+                RsaPublicKey {
+                    e: e.clone(),
+                    n: n.clone(),
+                },
+                RsaPrivateKey { d, n },
+            );
+        }
+    }
+}
+
+pub fn encrypt(key: &RsaPublicKey, m: &BigUint) -> BigUint {
+    m.modpow(&key.e, &key.n)
+}
+
+pub fn decrypt(key: &RsaPrivateKey, c: &BigUint) -> BigUint {
+    c.modpow(&key.d, &key.n)
+}
+
+pub fn get_parity_oracle(key: RsaPrivateKey) -> impl Fn(&BigUint) -> bool {
+    move |ct: &BigUint| decrypt(&key, ct).is_even()
+}
+
+// leaks the lowest `k` bits of the plain text, `k = 1` is the parity oracle
+pub fn get_lsb_oracle(key: RsaPrivateKey, k: u32) -> impl Fn(&BigUint) -> u64 {
+    assert!(k > 0 && k <= 64);
+    let mask = (BigUint::one() << k) - 1u32;
+
+    move |ct: &BigUint| {
+        let low = decrypt(&key, ct) & &mask;
+        low.iter_u64_digits().next().unwrap_or(0)
+    }
+}
+
+pub fn parity_attack(
+    key: &RsaPublicKey,
+    ct: &BigUint,
+    oracle: impl Fn(&BigUint) -> bool,
+    on_step: impl FnMut(&BigUint),
+) -> BigUint {
+    lsb_attack(key, ct, 1, |c| u64::from(!oracle(c)), on_step)
+}
+
+pub fn lsb_attack(
+    key: &RsaPublicKey,
+    ct: &BigUint,
+    k: u32,
+    oracle: impl Fn(&BigUint) -> u64,
+    mut on_step: impl FnMut(&BigUint),
+) -> BigUint {
+    let n = BigInt::from(key.n.clone());
+    let modulus = BigUint::one() << k;
+
+    // multiplying the cipher text by 2^(ke) multiplies the plain text by 2^k.
+    // as n is odd, the low bits of 2^k * m - j * n (mod n) tell us exactly how
+    // many times `j` we wrapped around the modulus, which picks one of 2^k
+    // slices of the current interval
+    let multiplier = BigUint::from(2u32).modpow(&(BigUint::from(k) * &key.e), &key.n);
+    let n_inv = invmod(&(&key.n % &modulus), &modulus).unwrap();
+
+    let mut lower = BigRational::zero();
+    let mut upper = BigRational::from_integer(n.clone());
+    let mut c = ct.clone();
+
+    let steps = key.n.bits().div_ceil(u64::from(k));
+    for _ in 0..steps {
+        c = (c * &multiplier) % &key.n;
+        let low_bits = BigUint::from(oracle(&c));
+
+        // j = -low_bits * n^-1 (mod 2^k)
+        let j = (&modulus - (low_bits * &n_inv) % &modulus) % &modulus;
+
+        let width = (&upper - &lower) / BigInt::from(modulus.clone());
+        lower += &width * BigInt::from(j.clone());
+        upper = &lower + &width;
+
+        on_step(&upper.to_integer().to_biguint().unwrap());
+    }
+
+    lower.ceil().to_integer().to_biguint().unwrap()
+}
+
+// size of the modulus in bytes
+pub fn key_len(n: &BigUint) -> usize {
+    n.bits().div_ceil(8) as usize
+}
+
+// EME-PKCS1-v1_5: 00 02 <at least 8 random non-zero bytes> 00 <message>
+pub fn pkcs1_pad(message: &[u8], k: usize) -> Vec<u8> {
+    assert!(message.len() + 11 <= k, "message too long");
+    let mut rng = rand::thread_rng();
+    let padding: Vec<u8> = (0..k - 3 - message.len())
+        .map(|_| rng.gen_range(1..=255))
+        .collect();
+
+    [&[0, 2], padding.as_slice(), &[0], message].concat()
+}
+
+pub fn pkcs1_unpad(em: &[u8]) -> Option<Vec<u8>> {
+    if em.len() < 11 || em[0] != 0 || em[1] != 2 || em[2..10].contains(&0) {
+        return None;
+    }
+    let separator = em[10..].iter().position(|b| *b == 0)? + 10;
+    Some(em[separator + 1..].to_vec())
+}
+
+// DER prefix for a SHA-256 DigestInfo
+const SHA256_DIGEST_INFO: [u8; 19] = [
+    0x30, 0x31, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01, 0x05,
+    0x00, 0x04, 0x20,
+];
+
+// EMSA-PKCS1-v1_5: 00 01 ff .. ff 00 <DigestInfo> <SHA-256(message)>
+pub fn pkcs1_sign_pad(message: &[u8], k: usize) -> Vec<u8> {
+    let digest = [SHA256_DIGEST_INFO.as_slice(), &sha256(message)].concat();
+    assert!(digest.len() + 11 <= k, "key too short");
+    [
+        &[0, 1],
+        vec![0xff; k - 3 - digest.len()].as_slice(),
+        &[0],
+        &digest,
+    ]
+    .concat()
+}
+
+pub fn sign(key: &RsaPrivateKey, message: &[u8]) -> BigUint {
+    let em = pkcs1_sign_pad(message, key_len(&key.n));
+    decrypt(key, &BigUint::from_bytes_be(&em))
+}
+
+pub fn verify(key: &RsaPublicKey, message: &[u8], signature: &BigUint) -> bool {
+    let em = pkcs1_sign_pad(message, key_len(&key.n));
+    signature < &key.n && encrypt(key, signature) == BigUint::from_bytes_be(&em)
+}
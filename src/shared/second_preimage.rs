@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+
+use rand::Rng;
+
+use super::md::{MdHash, BLOCK_SIZE};
+
+// Birthday search for a block from `a` and a block from `b` that land on the same
+// state. Returns both blocks, the common state and how many compressions it took.
+pub fn find_cross_collision(hash: &MdHash, a: u64, b: u64) -> (Vec<u8>, Vec<u8>, u64, usize) {
+    let mut rng = rand::thread_rng();
+    let mut from_a: HashMap<u64, Vec<u8>> = HashMap::new();
+    let mut from_b: HashMap<u64, Vec<u8>> = HashMap::new();
+    let mut calls = 0;
+
+    loop {
+        let block: [u8; BLOCK_SIZE] = rng.gen();
+        let next = hash.compress(a, &block);
+        calls += 1;
+        if let Some(other) = from_b.get(&next) {
+            break (block.to_vec(), other.clone(), next, calls);
+        }
+        from_a.insert(next, block.to_vec());
+
+        let block: [u8; BLOCK_SIZE] = rng.gen();
+        let next = hash.compress(b, &block);
+        calls += 1;
+        if let Some(other) = from_a.get(&next) {
+            break (other.clone(), block.to_vec(), next, calls);
+        }
+        from_b.insert(next, block.to_vec());
+    }
+}
+
+// k pairs of colliding messages, the i-th being a single block or 2^(k-1-i) + 1
+// blocks. Choosing one of each gives a message of any length from k to
+// k + 2^k - 1 blocks, all ending in the same state.
+#[derive(Debug, Clone)]
+pub struct ExpandableMessage {
+    pub pairs: Vec<(Vec<u8>, Vec<u8>)>,
+    pub state: u64,
+    pub calls: usize,
+}
+
+impl ExpandableMessage {
+    pub fn k(&self) -> usize {
+        self.pairs.len()
+    }
+
+    pub fn produce(&self, blocks: usize) -> Vec<u8> {
+        let k = self.k();
+        assert!(blocks >= k && blocks < k + (1 << k), "length out of range");
+
+        let extra = blocks - k;
+        self.pairs
+            .iter()
+            .enumerate()
+            .flat_map(|(i, (short, long))| {
+                if extra & (1 << (k - 1 - i)) != 0 {
+                    long.clone()
+                } else {
+                    short.clone()
+                }
+            })
+            .collect()
+    }
+}
+
+pub fn expandable_message(hash: &MdHash, k: usize) -> ExpandableMessage {
+    let mut expandable = ExpandableMessage {
+        pairs: Vec::new(),
+        state: hash.iv,
+        calls: 0,
+    };
+
+    for i in (0..k).rev() {
+        // the dummy prefix can be anything, only its final state matters
+        let dummy = vec![0; (1 << i) * BLOCK_SIZE];
+        let after_dummy = hash.compress_all(expandable.state, &dummy);
+
+        let (short, last, next, calls) = find_cross_collision(hash, expandable.state, after_dummy);
+        expandable.pairs.push((short, [dummy, last].concat()));
+        expandable.state = next;
+        expandable.calls += calls + (1 << i);
+    }
+    expandable
+}
+
+#[derive(Debug)]
+pub struct SecondPreimage {
+    pub message: Vec<u8>,
+    pub expandable_calls: usize,
+    pub bridge_calls: usize,
+}
+
+// Kelsey-Schneier: finds a different message with the same hash as `target`, which
+// needs at least k + 1 whole blocks and costs about k * 2^(b/2 + 1) + 2^(b - k).
+//
+// A single block from the end of an expandable message only has to hit any one of
+// the intermediate states of the target, rather than its final state. Then the
+// expandable message is stretched so the bridge sits at the same position as the
+// state it hit, and the padding (which includes the length) works out the same.
+pub fn second_preimage(hash: &MdHash, target: &[u8], k: usize) -> SecondPreimage {
+    let target_blocks = target.len() / BLOCK_SIZE;
+    assert!(target_blocks > k, "target too short for k");
+
+    // state after j blocks => j, for the positions an expandable message can reach
+    let mut state = hash.iv;
+    let mut intermediate: HashMap<u64, usize> = HashMap::new();
+    for (i, block) in target.chunks_exact(BLOCK_SIZE).enumerate() {
+        state = hash.compress(state, block);
+        let j = i + 1;
+        if j > k && j <= k + (1 << k) {
+            intermediate.entry(state).or_insert(j);
+        }
+    }
+
+    let expandable = expandable_message(hash, k);
+
+    let mut rng = rand::thread_rng();
+    let mut bridge_calls = 0;
+    let (bridge, j) = loop {
+        let block: [u8; BLOCK_SIZE] = rng.gen();
+        bridge_calls += 1;
+        if let Some(j) = intermediate.get(&hash.compress(expandable.state, &block)) {
+            break (block, *j);
+        }
+    };
+
+    let message = [
+        expandable.produce(j - 1).as_slice(),
+        &bridge,
+        &target[j * BLOCK_SIZE..],
+    ]
+    .concat();
+
+    SecondPreimage {
+        message,
+        expandable_calls: expandable.calls,
+        bridge_calls,
+    }
+}
@@ -0,0 +1,104 @@
+use num_bigint::{BigUint, RandBigInt};
+use num_traits::One;
+
+use super::{
+    bignum::{crt, invmod, small_factors},
+    dh::DhParams,
+    dlog::dlog_interval,
+    mac::hmac_sha256,
+};
+
+// a random element of order r in Z_p*, for a prime r dividing p - 1
+pub fn element_of_order(p: &BigUint, r: u64) -> BigUint {
+    let mut rng = rand::thread_rng();
+    let exponent = (p - 1u32) / r;
+    loop {
+        let h = rng
+            .gen_biguint_range(&BigUint::from(2u32), p)
+            .modpow(&exponent, p);
+        if !h.is_one() {
+            break h;
+        }
+    }
+}
+
+// Sending h of order r as our public key confines the shared secret to h's r
+// possible powers, so the tag gives away x mod r after at most r guesses.
+pub fn recover_residue(
+    p: &BigUint,
+    r: u64,
+    oracle: impl Fn(&BigUint) -> (Vec<u8>, Vec<u8>),
+) -> BigUint {
+    let h = element_of_order(p, r);
+    let (message, tag) = oracle(&h);
+
+    let mut k = BigUint::one();
+    for x in 0..r {
+        if hmac_sha256(&k.to_bytes_be(), &message) == tag {
+            return BigUint::from(x);
+        }
+        k = k * &h % p;
+    }
+    unreachable!("h has order r, so one of its powers is the key")
+}
+
+#[derive(Debug)]
+pub struct SubgroupResult {
+    // the secret is `residue` mod `modulus`
+    pub residue: BigUint,
+    pub modulus: BigUint,
+    pub queries: usize,
+}
+
+// Pohlig-Hellman against Bob: x mod each prime factor of (p - 1) / q below `bound`,
+// until the product of the factors passes q, combined with the CRT. If they never
+// pass q we only learn x mod their product.
+pub fn small_subgroup_attack(
+    params: &DhParams,
+    bound: u64,
+    oracle: impl Fn(&BigUint) -> (Vec<u8>, Vec<u8>),
+) -> SubgroupResult {
+    let j = (&params.p - 1u32) / &params.q;
+
+    let mut residues: Vec<(BigUint, BigUint)> = Vec::new();
+    let mut modulus = BigUint::one();
+    for r in small_factors(&j, bound) {
+        if modulus > params.q {
+            break;
+        }
+        residues.push((recover_residue(&params.p, r, &oracle), BigUint::from(r)));
+        modulus *= r;
+    }
+
+    let (residue, modulus) = crt(&residues);
+    SubgroupResult {
+        residue,
+        modulus,
+        queries: residues.len(),
+    }
+}
+
+// When the small factors only give x = n mod r, the rest is an interval search:
+// y * g^-n = (g^r)^m for some m in [0, (q - 1) / r], which the kangaroo finds in
+// about sqrt(q / r) steps. Then x = n + m * r.
+pub fn subgroup_kangaroo_attack(
+    params: &DhParams,
+    public: &BigUint,
+    bound: u64,
+    oracle: impl Fn(&BigUint) -> (Vec<u8>, Vec<u8>),
+) -> Option<BigUint> {
+    let SubgroupResult {
+        residue: n,
+        modulus: r,
+        ..
+    } = small_subgroup_attack(params, bound, oracle);
+    if r > params.q {
+        return Some(n);
+    }
+
+    let p = &params.p;
+    let g = params.g.modpow(&r, p);
+    let y = public * invmod(&params.g.modpow(&n, p), p)? % p;
+    let m = dlog_interval(&g, &y, &BigUint::from(0u32), &((&params.q - 1u32) / &r), p)?;
+    Some(n + m * r)
+}
@@ -0,0 +1,194 @@
+use rand::Rng;
+
+use super::{
+    error::CryptoError,
+    gcm::compute_tag,
+    gf128::Gf128,
+    gf2::{Gf2Matrix, Gf2Vector},
+};
+
+// Bob only checks the first tag_bytes of a GCM tag, and says whether a ciphertext
+// under his key and nonce, with no associated data, is authentic
+pub fn get_truncated_gcm_oracle(
+    key: Vec<u8>,
+    iv: Vec<u8>,
+    tag_bytes: usize,
+) -> Result<impl Fn(&[u8], &[u8]) -> bool, CryptoError> {
+    // a key, nonce or tag length he can't use is turned away up front
+    if !(1..=16).contains(&tag_bytes) {
+        return Err(CryptoError::BadLength(tag_bytes));
+    }
+    compute_tag(&key, &iv, &[], &[])?;
+    Ok(move |ciphertext: &[u8], tag: &[u8]| {
+        compute_tag(&key, &iv, &[], ciphertext).is_ok_and(|t| t[..tag_bytes] == *tag)
+    })
+}
+
+// multiplication by x, a right shift with R folded back in
+fn mul_x(a: Gf128) -> Gf128 {
+    let shifted = Gf128(a.0 >> 1);
+    if a.0 & 1 == 1 {
+        shifted + Gf128(0xe1 << 120)
+    } else {
+        shifted
+    }
+}
+
+// a^(2^i)
+fn square_times(a: Gf128, i: usize) -> Gf128 {
+    (0..i).fold(a, |acc, _| acc * acc)
+}
+
+#[derive(Debug, Clone)]
+pub struct FergusonIteration {
+    // how many bits of H were known going in
+    pub known_bits: usize,
+    // the number of tag bits the forgeries left untouched for any H that fits
+    pub zeroed_rows: usize,
+    pub tag_bits: usize,
+    pub queries: usize,
+}
+
+impl FergusonIteration {
+    // The chance of each forgery going through, when the tag bits that weren't
+    // zeroed have to come out right by luck. About 1 in this many queries succeeds.
+    pub fn expected_success_rate(&self) -> f64 {
+        0.5f64.powi((self.tag_bits - self.zeroed_rows) as i32)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FergusonResult {
+    pub h: Gf128,
+    pub iterations: Vec<FergusonIteration>,
+}
+
+// Ferguson's attack on short GCM tags. Flipping bits of the ciphertext blocks
+// multiplied by H^(2^i) changes the tag by sum(e_i * H^(2^i)), which is linear in
+// the bits of H as squaring is: Ad * h for a matrix Ad = sum(M(e_i) * S^i). Ad is
+// linear in the bits of the e_i in turn, so solving for e_i that zero the first
+// rows of Ad * X, where H is known to be in the span of X, gives forgeries that
+// only leave the remaining tag bits to chance. Each forgery that gets through says
+// those rows of Ad are orthogonal to h, which shrinks X and lets more rows be
+// zeroed the next time around, until only H itself is left. Answers that no H
+// fits are put down to the oracle.
+pub fn ferguson_attack(
+    ciphertext: &[u8],
+    tag: &[u8],
+    oracle: impl Fn(&[u8], &[u8]) -> bool,
+) -> Result<FergusonResult, CryptoError> {
+    // only whole blocks can be flipped, and there has to be at least one
+    if ciphertext.is_empty() || !ciphertext.len().is_multiple_of(16) {
+        return Err(CryptoError::BadLength(ciphertext.len()));
+    }
+    if !(1..=16).contains(&tag.len()) {
+        return Err(CryptoError::BadLength(tag.len()));
+    }
+    let blocks = ciphertext.len() / 16;
+    let tag_bits = tag.len() * 8;
+
+    // With the length block last, the block at offset 16 * (blocks + 1 - 2^i) is
+    // multiplied by H^(2^i). Use every power of two there is room for.
+    let n = (blocks + 1).ilog2() as usize;
+    let offset = |i: usize| 16 * (blocks + 1 - (1 << i));
+
+    let mut rng = rand::thread_rng();
+    let mut known: Vec<Gf2Vector> = Vec::new();
+    let mut iterations = Vec::new();
+    loop {
+        // the h that satisfy everything learned so far, as the columns of X
+        let x = Gf2Matrix::from_rows(128, known.clone()).kernel();
+        match x.len() {
+            0 => return Err(CryptoError::OracleRejection),
+            1 => {
+                return Ok(FergusonResult {
+                    h: x[0].to_gf128(),
+                    iterations,
+                })
+            }
+            _ => {}
+        }
+
+        // as many rows as there are unknowns to zero them with, short of the
+        // whole tag, so that a success still teaches something
+        let zeroed = (tag_bits - 1).min((n * 128 - 1) / x.len());
+
+        // The dependency matrix T, with a column for each bit of the e_i giving the
+        // first rows of M(x^b) * S^i * X for that bit alone. Multiplying by x^b is
+        // a matter of b shifts, so each column of S^i * X is walked through them.
+        let mut columns = Vec::with_capacity(n * 128);
+        for i in 1..=n {
+            let squared: Vec<Gf128> = x.iter().map(|v| square_times(v.to_gf128(), i)).collect();
+            let mut shifted = squared;
+            for _ in 0..128 {
+                let mut column = Gf2Vector::zeros(zeroed * x.len());
+                for (k, v) in shifted.iter_mut().enumerate() {
+                    for row in 0..zeroed {
+                        column.set(k * zeroed + row, (v.0 >> (127 - row)) & 1 == 1);
+                    }
+                    *v = mul_x(*v);
+                }
+                columns.push(column);
+            }
+        }
+        let dependencies = Gf2Matrix::from_columns(zeroed * x.len(), &columns).kernel();
+
+        let mut queries = 0;
+        let errors = loop {
+            let mut combination = Gf2Vector::zeros(n * 128);
+            dependencies
+                .iter()
+                .filter(|_| rng.gen())
+                .for_each(|v| combination.add_assign(v));
+            if combination.is_zero() {
+                continue;
+            }
+
+            let errors: Vec<Gf128> = (0..n)
+                .map(|i| {
+                    let bits = Gf2Vector {
+                        len: 128,
+                        words: combination.words[2 * i..2 * i + 2].to_vec(),
+                    };
+                    bits.to_gf128()
+                })
+                .collect();
+            let mut forged = ciphertext.to_vec();
+            for (i, e) in errors.iter().enumerate() {
+                let start = offset(i + 1);
+                forged[start..start + 16]
+                    .iter_mut()
+                    .zip(e.to_bytes())
+                    .for_each(|(c, e)| *c ^= e);
+            }
+
+            queries += 1;
+            if oracle(&forged, tag) {
+                break errors;
+            }
+        };
+
+        iterations.push(FergusonIteration {
+            known_bits: 128 - x.len(),
+            zeroed_rows: zeroed,
+            tag_bits,
+            queries,
+        });
+
+        // Ad, column by column, and the rows past the zeroed ones that came out 0
+        let ad_columns: Vec<Gf2Vector> = (0..128)
+            .map(|j| {
+                let basis = Gf128(1 << (127 - j));
+                let image = errors
+                    .iter()
+                    .enumerate()
+                    .fold(Gf128::zero(), |acc, (i, e)| {
+                        acc + *e * square_times(basis, i + 1)
+                    });
+                Gf2Vector::from_gf128(image)
+            })
+            .collect();
+        let ad = Gf2Matrix::from_columns(128, &ad_columns);
+        known.extend(ad.rows[zeroed..tag_bits].iter().cloned());
+    }
+}
@@ -0,0 +1,200 @@
+use num_bigint::{BigUint, RandBigInt};
+use num_traits::{One, Zero};
+
+use super::{
+    bignum::{crt, small_factors, sqrt_mod},
+    dlog::{kangaroo_in, Jumps},
+    ec::{EcParams, Point},
+    mac::hmac_sha256,
+    montgomery::MontgomeryCurve,
+};
+
+// A curve and its quadratic twist have 2p + 2 points between them
+pub fn twist_order(p: &BigUint, curve_order: &BigUint) -> BigUint {
+    2u32 * p + 2u32 - curve_order
+}
+
+// a u with no v on the curve, which the ladder will take to be on the twist
+pub fn random_twist_u(curve: &MontgomeryCurve) -> BigUint {
+    let mut rng = rand::thread_rng();
+    loop {
+        let u = rng.gen_biguint_below(&curve.p);
+        if sqrt_mod(&curve.rhs(&u), &curve.p).is_none() {
+            break u;
+        }
+    }
+}
+
+// u of a twist point whose order is the product of `factors`, distinct primes
+// that each divide the twist order once
+pub fn twist_point_of_order(
+    curve: &MontgomeryCurve,
+    twist_order: &BigUint,
+    factors: &[u64],
+) -> BigUint {
+    let order: BigUint = factors.iter().map(|r| BigUint::from(*r)).product();
+    let cofactor = twist_order / &order;
+
+    loop {
+        let u = curve.ladder(&random_twist_u(curve), &cofactor);
+        if factors
+            .iter()
+            .all(|r| !curve.ladder(&u, &(&order / *r)).is_zero())
+        {
+            break u;
+        }
+    }
+}
+
+// The key only depends on u, which is the same for kP and -kP, so this gives
+// Bob's secret mod r up to sign: the answer is in [0, r / 2]. Walks through the
+// multiples of h with differential additions rather than a ladder for each.
+pub fn recover_residue(
+    curve: &MontgomeryCurve,
+    h: &BigUint,
+    r: u64,
+    oracle: impl Fn(&BigUint) -> (Vec<u8>, Vec<u8>),
+) -> BigUint {
+    let (message, tag) = oracle(h);
+    let matches = |u: &BigUint| hmac_sha256(&u.to_bytes_be(), &message) == tag;
+
+    // u(0 * h) is the identity, which the ladder hands back as 0
+    if matches(&BigUint::zero()) {
+        return BigUint::zero();
+    }
+
+    let (mut previous, mut current) = (h.clone(), curve.ladder(h, &BigUint::from(2u32)));
+    if matches(h) {
+        return BigUint::one();
+    }
+    for k in 2..=r / 2 {
+        if matches(&current) {
+            return BigUint::from(k);
+        }
+        let next = curve.differential_add(&current, h, &previous);
+        (previous, current) = (current, next);
+    }
+    unreachable!("h has order r, so one of its multiples is the key")
+}
+
+// Bob's ladder runs on the twist for any u that is not on his curve, and the
+// twist's order has plenty of small factors. Those give his secret x mod each of
+// them up to sign. Against one anchor factor r0, sending a point of order r0 * r
+// and seeing which of the two combinations matches fixes the relative signs.
+// Returns N and R with x = +-N mod R, and the number of queries.
+pub fn twist_residues(
+    curve: &MontgomeryCurve,
+    twist_order: &BigUint,
+    bound: u64,
+    oracle: impl Fn(&BigUint) -> (Vec<u8>, Vec<u8>),
+) -> (BigUint, BigUint, usize) {
+    // the power of 2 is shared with the curve, leave it out
+    let factors: Vec<u64> = small_factors(twist_order, bound)
+        .into_iter()
+        .filter(|r| *r > 2 && !(twist_order % (r * r)).is_zero())
+        .collect();
+
+    let mut residues: Vec<(BigUint, BigUint)> = factors
+        .iter()
+        .map(|r| {
+            let h = twist_point_of_order(curve, twist_order, &[*r]);
+            (recover_residue(curve, &h, *r, &oracle), BigUint::from(*r))
+        })
+        .collect();
+    let mut queries = residues.len();
+
+    // a zero residue has no sign, and cannot anchor anything
+    if let Some(anchor) = residues.iter().position(|(n, _)| !n.is_zero()) {
+        let (n0, r0) = residues[anchor].clone();
+        for i in (0..residues.len()).filter(|i| *i != anchor) {
+            let (n, r) = residues[i].clone();
+            if n.is_zero() {
+                continue;
+            }
+
+            let h = twist_point_of_order(curve, twist_order, &[factors[anchor], factors[i]]);
+            let (message, tag) = oracle(&h);
+            queries += 1;
+
+            let (combined, _) = crt(&[(n0.clone(), r0.clone()), (n.clone(), r.clone())]);
+            let key = curve.ladder(&h, &combined);
+            if hmac_sha256(&key.to_bytes_be(), &message) != tag {
+                residues[i].0 = &r - n;
+            }
+        }
+    }
+
+    let (n, r) = crt(&residues);
+    (n, r, queries)
+}
+
+#[derive(Debug)]
+pub struct TwistResult {
+    // either Bob's secret or q minus it, which an x-only protocol cannot tell apart
+    pub secret: BigUint,
+    pub queries: usize,
+}
+
+// With x = +-N mod R from the twist, the rest is a kangaroo on the Weierstrass
+// form of the curve for m with +-Y - N * G = m * (R * G). Which sign is unknown, so
+// each jump function is tried on both before moving on to the next.
+pub fn twist_attack(
+    params: &EcParams,
+    curve: &MontgomeryCurve,
+    twist_order: &BigUint,
+    public: &BigUint,
+    bound: u64,
+    oracle: impl Fn(&BigUint) -> (Vec<u8>, Vec<u8>),
+) -> Option<TwistResult> {
+    let most = &params.q - 1u32;
+    twist_attack_in(params, curve, twist_order, public, &most, bound, oracle)
+}
+
+// The same, for a secret known to be at most `most`, which narrows the kangaroo.
+// Only one of x and q - x need be that small, so both signs of the public point
+// are tried, after the one that does for the full range.
+pub fn twist_attack_in(
+    params: &EcParams,
+    curve: &MontgomeryCurve,
+    twist_order: &BigUint,
+    public: &BigUint,
+    most: &BigUint,
+    bound: u64,
+    oracle: impl Fn(&BigUint) -> (Vec<u8>, Vec<u8>),
+) -> Option<TwistResult> {
+    let (n, r, queries) = twist_residues(curve, twist_order, bound, oracle);
+
+    // Bob's public key as a full point, with whichever sign of v
+    let weierstrass = &params.curve;
+    let x = curve.to_weierstrass_x(public);
+    let y = sqrt_mod(&weierstrass.rhs(&x), &curve.p)?;
+    let public = Point::Affine(x, y);
+    let negated = weierstrass.negate(&public);
+
+    let g = weierstrass.scalar_mul(&params.g, &r);
+    let zero = BigUint::zero();
+    let upper = most / &r;
+    let candidates: Vec<(BigUint, Point)> = [&public, &negated]
+        .into_iter()
+        .flat_map(|y| [(y, n.clone()), (y, (&r - &n) % &r)])
+        .map(|(y, n)| {
+            let shifted = weierstrass.add(
+                y,
+                &weierstrass.negate(&weierstrass.scalar_mul(&params.g, &n)),
+            );
+            (n, shifted)
+        })
+        .collect();
+
+    let k = Jumps::for_interval(&zero, &upper).sizes.len() as u32;
+    (k..k + 3).find_map(|k| {
+        let jumps = Jumps::powers_of_two(k);
+        candidates.iter().find_map(|(n, shifted)| {
+            let m = kangaroo_in(weierstrass, &g, shifted, &zero, &upper, &jumps)?;
+            Some(TwistResult {
+                secret: (n + m * &r) % &params.q,
+                queries,
+            })
+        })
+    })
+}
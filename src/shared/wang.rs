@@ -0,0 +1,356 @@
+use rand::Rng;
+
+use super::md4::{
+    f, g, md4_compress, md4_steps, round_1, words_to_block, IV, ROUND_1_SHIFTS, ROUND_2_CONSTANT,
+};
+
+// Indexes into the chaining values of a block: the initial a0, d0, c0, b0, then
+// every step in order as returned by `md4_steps`.
+const B0: usize = 3;
+const A1: usize = 4;
+const D1: usize = 5;
+const C1: usize = 6;
+const B1: usize = 7;
+const A2: usize = 8;
+const D2: usize = 9;
+const C2: usize = 10;
+const B2: usize = 11;
+const A3: usize = 12;
+const D3: usize = 13;
+const C3: usize = 14;
+const B3: usize = 15;
+const A4: usize = 16;
+const D4: usize = 17;
+const C4: usize = 18;
+const B4: usize = 19;
+const A5: usize = 20;
+const D5: usize = 21;
+const C5: usize = 22;
+const B5: usize = 23;
+const A6: usize = 24;
+const D6: usize = 25;
+const C6: usize = 26;
+const B9: usize = 39;
+const A10: usize = 40;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Requirement {
+    Zero,
+    One,
+    // equal to the same bit of another chaining value
+    Equal(usize),
+    NotEqual(usize),
+}
+
+// One of the sufficient conditions from Wang et al., "Cryptanalysis of the Hash
+// Functions MD4 and RIPEMD". Bits count from 1 like the paper does.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Condition {
+    pub value: usize,
+    pub bit: u32,
+    pub requirement: Requirement,
+}
+
+const fn c(value: usize, bit: u32, requirement: Requirement) -> Condition {
+    Condition {
+        value,
+        bit,
+        requirement,
+    }
+}
+
+use Requirement::{Equal, NotEqual, One, Zero};
+
+pub const ROUND_1_CONDITIONS: &[Condition] = &[
+    c(A1, 7, Equal(B0)),
+    c(D1, 7, Zero),
+    c(D1, 8, Equal(A1)),
+    c(D1, 11, Equal(A1)),
+    c(C1, 7, One),
+    c(C1, 8, One),
+    c(C1, 11, Zero),
+    c(C1, 26, Equal(D1)),
+    c(B1, 7, One),
+    c(B1, 8, Zero),
+    c(B1, 11, Zero),
+    c(B1, 26, Zero),
+    c(A2, 8, One),
+    c(A2, 11, One),
+    c(A2, 26, Zero),
+    c(A2, 14, Equal(B1)),
+    c(D2, 14, Zero),
+    c(D2, 19, Equal(A2)),
+    c(D2, 20, Equal(A2)),
+    c(D2, 21, Equal(A2)),
+    c(D2, 22, Equal(A2)),
+    c(D2, 26, One),
+    c(C2, 13, Equal(D2)),
+    c(C2, 14, Zero),
+    c(C2, 15, Equal(D2)),
+    c(C2, 19, Zero),
+    c(C2, 20, Zero),
+    c(C2, 21, One),
+    c(C2, 22, Zero),
+    c(B2, 13, One),
+    c(B2, 14, One),
+    c(B2, 15, Zero),
+    c(B2, 17, Equal(C2)),
+    c(B2, 19, Zero),
+    c(B2, 20, Zero),
+    c(B2, 21, Zero),
+    c(B2, 22, Zero),
+    c(A3, 13, One),
+    c(A3, 14, One),
+    c(A3, 15, One),
+    c(A3, 17, Zero),
+    c(A3, 19, Zero),
+    c(A3, 20, Zero),
+    c(A3, 21, Zero),
+    c(A3, 22, One),
+    c(A3, 23, Equal(B2)),
+    c(A3, 26, Equal(B2)),
+    c(D3, 13, One),
+    c(D3, 14, One),
+    c(D3, 15, One),
+    c(D3, 17, Zero),
+    c(D3, 20, Zero),
+    c(D3, 21, One),
+    c(D3, 22, One),
+    c(D3, 23, Zero),
+    c(D3, 26, One),
+    c(D3, 30, Equal(A3)),
+    c(C3, 17, One),
+    c(C3, 20, Zero),
+    c(C3, 21, Zero),
+    c(C3, 22, Zero),
+    c(C3, 23, Zero),
+    c(C3, 26, Zero),
+    c(C3, 30, One),
+    c(C3, 32, Equal(D3)),
+    c(B3, 20, Zero),
+    c(B3, 21, One),
+    c(B3, 22, One),
+    c(B3, 23, Equal(C3)),
+    c(B3, 26, One),
+    c(B3, 30, Zero),
+    c(B3, 32, Zero),
+    c(A4, 23, Zero),
+    c(A4, 26, Zero),
+    c(A4, 27, Equal(B3)),
+    c(A4, 29, Equal(B3)),
+    c(A4, 30, One),
+    c(A4, 32, Zero),
+    c(D4, 23, Zero),
+    c(D4, 26, Zero),
+    c(D4, 27, One),
+    c(D4, 29, One),
+    c(D4, 30, Zero),
+    c(D4, 32, One),
+    c(C4, 19, Equal(D4)),
+    c(C4, 23, One),
+    c(C4, 26, One),
+    c(C4, 27, Zero),
+    c(C4, 29, Zero),
+    c(C4, 30, Zero),
+    c(B4, 19, Zero),
+    c(B4, 26, Equal(C4)),
+    c(B4, 27, One),
+    c(B4, 29, One),
+    c(B4, 30, Zero),
+];
+
+pub const A5_CONDITIONS: &[Condition] = &[
+    c(A5, 19, Equal(C4)),
+    c(A5, 26, One),
+    c(A5, 27, Zero),
+    c(A5, 29, One),
+    c(A5, 32, One),
+];
+
+pub const D5_CONDITIONS: &[Condition] = &[
+    c(D5, 19, Equal(A5)),
+    c(D5, 26, Equal(B4)),
+    c(D5, 27, Equal(B4)),
+    c(D5, 29, Equal(B4)),
+    c(D5, 32, Equal(B4)),
+];
+
+// left to chance, these are what the search loop is paying for
+pub const UNENFORCED_CONDITIONS: &[Condition] = &[
+    c(C5, 26, Equal(D5)),
+    c(C5, 27, Equal(D5)),
+    c(C5, 29, Equal(D5)),
+    c(C5, 30, Equal(D5)),
+    c(C5, 32, Equal(D5)),
+    c(B5, 29, Equal(C5)),
+    c(B5, 30, One),
+    c(B5, 32, Zero),
+    c(A6, 29, One),
+    c(A6, 32, One),
+    c(D6, 29, Equal(B5)),
+    c(C6, 29, Equal(D6)),
+    c(C6, 30, NotEqual(D6)),
+    c(C6, 32, NotEqual(D6)),
+    c(B9, 32, One),
+    c(A10, 32, One),
+];
+
+fn chaining_values(words: &[u32; 16]) -> Vec<u32> {
+    let mut values = vec![IV[0], IV[3], IV[2], IV[1]];
+    values.extend(md4_steps(IV, words));
+    values
+}
+
+fn bit(x: u32, bit: u32) -> u32 {
+    (x >> (bit - 1)) & 1
+}
+
+pub fn holds(values: &[u32], condition: &Condition) -> bool {
+    let x = bit(values[condition.value], condition.bit);
+    match condition.requirement {
+        Zero => x == 0,
+        One => x == 1,
+        Equal(other) => x == bit(values[other], condition.bit),
+        NotEqual(other) => x != bit(values[other], condition.bit),
+    }
+}
+
+// rewrites `values[value]` so that all of `conditions` on it hold
+fn enforce(values: &[u32], value: usize, conditions: &[Condition]) -> u32 {
+    conditions
+        .iter()
+        .filter(|condition| condition.value == value)
+        .fold(values[value], |x, condition| {
+            let mask = 1 << (condition.bit - 1);
+            let wanted = match condition.requirement {
+                Zero => 0,
+                One => mask,
+                Equal(other) => values[other] & mask,
+                NotEqual(other) => !values[other] & mask,
+            };
+            (x & !mask) | wanted
+        })
+}
+
+// the message word that makes round 1 step `step` produce `values[4 + step]`
+fn solve_round_1(values: &[u32], step: usize) -> u32 {
+    let i = 4 + step;
+    let (a, b, c, d) = (values[i - 4], values[i - 1], values[i - 2], values[i - 3]);
+    values[i]
+        .rotate_right(ROUND_1_SHIFTS[step % 4])
+        .wrapping_sub(a)
+        .wrapping_sub(f(b, c, d))
+}
+
+// Single-step modification: every round 1 value is a function of one message
+// word, so compute it, fix the bits the conditions talk about, and solve for the
+// word that produces the fixed value instead.
+pub fn round_1_modification(words: &mut [u32; 16]) {
+    let mut values = vec![IV[0], IV[3], IV[2], IV[1]];
+    for step in 0..16 {
+        let i = values.len();
+        let (a, b, c, d) = (values[i - 4], values[i - 1], values[i - 2], values[i - 3]);
+        values.push(round_1(a, b, c, d, words[step], ROUND_1_SHIFTS[step % 4]));
+
+        values[i] = enforce(&values, i, ROUND_1_CONDITIONS);
+        words[step] = solve_round_1(&values, step);
+    }
+}
+
+// Multi-step modification of a5 and d5. Fixing a5 means changing m0, which also
+// changes a1; m1..m4 are then solved again so that d1, c1, b1 and a2 (and so the
+// rest of round 1) come out as before. d5 does the same through m4, a2 and m5..m8.
+// The bits touched in a1 and a2 are ones round 1 has no conditions on, barring
+// the odd carry.
+pub fn round_2_modification(words: &mut [u32; 16]) {
+    for (value, word, shift, round_1_value, conditions) in
+        [(A5, 0, 3, A1, A5_CONDITIONS), (D5, 4, 5, A2, D5_CONDITIONS)]
+    {
+        let mut values = chaining_values(words);
+        let fixed = enforce(&values, value, conditions);
+        if fixed == values[value] {
+            continue;
+        }
+
+        let (a, b, c, d) = (
+            values[value - 4],
+            values[value - 1],
+            values[value - 2],
+            values[value - 3],
+        );
+        words[word] = fixed
+            .rotate_right(shift)
+            .wrapping_sub(a)
+            .wrapping_sub(g(b, c, d))
+            .wrapping_sub(ROUND_2_CONSTANT);
+
+        let i = round_1_value;
+        values[i] = round_1(
+            values[i - 4],
+            values[i - 1],
+            values[i - 2],
+            values[i - 3],
+            words[word],
+            ROUND_1_SHIFTS[0],
+        );
+        (word + 1..=word + 4).for_each(|step| words[step] = solve_round_1(&values, step));
+    }
+}
+
+// the differential: M' = M + (2^31) in m1, (2^31 - 2^28) in m2 and (-2^16) in m12
+pub fn apply_differential(words: &[u32; 16]) -> [u32; 16] {
+    let mut other = *words;
+    other[1] = other[1].wrapping_add(1 << 31);
+    other[2] = other[2].wrapping_add(1 << 31).wrapping_sub(1 << 28);
+    other[12] = other[12].wrapping_sub(1 << 16);
+    other
+}
+
+#[derive(Debug)]
+pub struct Md4Collision {
+    pub messages: [Vec<u8>; 2],
+    pub attempts: usize,
+    // attempts after which every round 1, a5 and d5 condition actually held
+    pub enforced_held: usize,
+    // of those, attempts where the unenforced conditions held too
+    pub all_held: usize,
+}
+
+pub fn enforced_conditions() -> Vec<Condition> {
+    [ROUND_1_CONDITIONS, A5_CONDITIONS, D5_CONDITIONS].concat()
+}
+
+pub fn find_md4_collision() -> Md4Collision {
+    let mut rng = rand::thread_rng();
+    let enforced = enforced_conditions();
+    let mut attempts = 0;
+    let mut enforced_held = 0;
+    let mut all_held = 0;
+
+    loop {
+        attempts += 1;
+        let mut words: [u32; 16] = rng.gen();
+        round_1_modification(&mut words);
+        round_2_modification(&mut words);
+
+        let values = chaining_values(&words);
+        if enforced.iter().all(|c| holds(&values, c)) {
+            enforced_held += 1;
+            if UNENFORCED_CONDITIONS.iter().all(|c| holds(&values, c)) {
+                all_held += 1;
+            }
+        }
+
+        // both are a single block of the same length, so the padding block is the
+        // same and colliding compressions are enough
+        let other = apply_differential(&words);
+        if md4_compress(IV, &words) == md4_compress(IV, &other) {
+            break Md4Collision {
+                messages: [words_to_block(&words), words_to_block(&other)],
+                attempts,
+                enforced_held,
+                all_held,
+            };
+        }
+    }
+}
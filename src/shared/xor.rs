@@ -1,12 +1,28 @@
+use super::error::CryptoError;
+
 pub fn xor(a: &[u8], b: &[u8]) -> Vec<u8> {
     assert_eq!(a.len(), b.len());
     (0..a.len()).map(|i| a[i] ^ b[i]).collect()
 }
 
+// for buffers from outside, which need not be the same length
+pub fn fixed_xor(a: &[u8], b: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    if a.len() != b.len() {
+        return Err(CryptoError::LengthMismatch {
+            expected: a.len(),
+            actual: b.len(),
+        });
+    }
+    Ok(xor(a, b))
+}
+
 pub fn xor_with_key(a: &[u8], key: u8) -> Vec<u8> {
     (0..a.len()).map(|i| a[i] ^ key).collect()
 }
 
-pub fn xor_with_repeating_key(a: &[u8], key: &[u8]) -> Vec<u8> {
-    (0..a.len()).map(|i| a[i] ^ key[i % key.len()]).collect()
+pub fn xor_with_repeating_key(a: &[u8], key: &[u8]) -> Result<Vec<u8>, CryptoError> {
+    if key.is_empty() {
+        return Err(CryptoError::InvalidKeyLength(0));
+    }
+    Ok((0..a.len()).map(|i| a[i] ^ key[i % key.len()]).collect())
 }